@@ -3,7 +3,7 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use cosmoscrow::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, EscrowResponse, EscrowListResponse, MigrateMsg};
+use cosmoscrow::msg::{ActionableResponse, ApprovalListResponse, ApprovalStatusResponse, AccruedFeesResponse, CanCancelResponse, CapabilitiesResponse, ExecuteMsg, InstantiateMsg, InvariantReport, QueryMsg, EscrowResponse, EscrowListResponse, EscrowHistoryResponse, ConfigResponse, MigrateMsg, OwnershipResponse, ReconciliationResponse, RoleResponse, StatsResponse, TemplateListResponse};
 use cosmoscrow::state::Escrow;
 
 fn main() {
@@ -17,6 +17,20 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(EscrowResponse), &out_dir);
     export_schema(&schema_for!(EscrowListResponse), &out_dir);
+    export_schema(&schema_for!(ActionableResponse), &out_dir);
+    export_schema(&schema_for!(EscrowHistoryResponse), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(CapabilitiesResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(ReconciliationResponse), &out_dir);
+    export_schema(&schema_for!(InvariantReport), &out_dir);
+    export_schema(&schema_for!(OwnershipResponse), &out_dir);
+    export_schema(&schema_for!(RoleResponse), &out_dir);
+    export_schema(&schema_for!(TemplateListResponse), &out_dir);
+    export_schema(&schema_for!(ApprovalStatusResponse), &out_dir);
+    export_schema(&schema_for!(ApprovalListResponse), &out_dir);
+    export_schema(&schema_for!(CanCancelResponse), &out_dir);
+    export_schema(&schema_for!(AccruedFeesResponse), &out_dir);
     export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(Escrow), &out_dir);
 }
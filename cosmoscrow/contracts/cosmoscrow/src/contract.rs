@@ -1,32 +1,119 @@
 use cosmwasm_std::{
-    entry_point, to_json_binary, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Order,
-    Response, StdResult,
+    entry_point, to_json_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, Event, Int256, MessageInfo, Order,
+    Reply, Response, StdError, StdResult, Storage, SubMsg, SubMsgResult, Uint128, Uint256, WasmMsg,
 };
-use cw_storage_plus::Bound;
-use cw2::set_contract_version;
+use cw_storage_plus::{Bound, Map};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use cw2::{get_contract_version, set_contract_version};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, EscrowResponse, EscrowListResponse, MigrateMsg};
-use crate::state::{Escrow, ESCROW_COUNTER, ESCROWS, ESCROWS_BY_CREATOR, ESCROWS_BY_BENEFICIARY, ESCROWS_BY_APPROVER};
+use crate::helpers::approval_sign_bytes;
+use crate::msg::{ActionableEscrow, ActionableResponse, ApprovalListResponse, ApprovalStatusResponse, AccruedFeesResponse, CanCancelResponse, CapabilitiesResponse, ConfigResponse, CreateEscrowMsg, EscrowAction, EscrowEventResponse, EscrowHistoryResponse, EscrowRole, ExecuteMsg, InstantiateMsg, InvariantReport, OwnershipAction, OwnershipResponse, QueryMsg, ReconciliationResponse, SortKey, SortOrder, TemplateListResponse, TemplateResponse, EscrowResponse, EscrowListResponse, MigrateMsg, RoleResponse, StatsResponse};
+use crate::state::{Approval, BeneficiaryChange, ApprovalMode, ApprovalPolicy, ApprovalTier, APPROVAL_DELEGATES, APPROVAL_KEYS, APPROVAL_NONCES, Config, FeeRounding, DENOM_ALIASES, EmergencyWithdraw, Escrow, EscrowEvent, EscrowEventKind, EscrowStatus, PendingOwner, RateLimit, RemainderPolicy, Settlement, Stats, Template, TEMPLATES, CONFIG, EMERGENCY_WITHDRAWALS, EMERGENCY_WITHDRAW_DELAY, CREATION_COUNTS, CREATOR_ALLOWLIST, PENDING_OWNER, DEFAULT_MAX_DESCRIPTION_LENGTH, MAX_APPROVERS, MAX_COMMENT_LENGTH, MAX_METADATA_SIZE, MAX_BATCH_SIZE, MAX_BENEFICIARIES, MAX_SORT_CANDIDATES, MAX_SORT_SCAN, ESCROW_COUNTER, FEE_ACCRUED, STATS, TOTAL_LOCKED, ESCROWS, ESCROWS_BY_CREATOR, ESCROWS_BY_BENEFICIARY, ESCROWS_BY_APPROVER, ESCROWS_BY_CREATED_AT, ESCROWS_BY_DENOM, ESCROWS_BY_EXPIRES_AT, ESCROW_HISTORY, ESCROWS_V1, ESCROWS_V2, ESCROWS_V4, IDEMPOTENCY_KEYS, LEGACY_ESCROWS_BY_APPROVER, LEGACY_ESCROWS_BY_BENEFICIARY, LEGACY_ESCROWS_BY_CREATOR};
 
 // Version info for migration
 const CONTRACT_NAME: &str = "crates.io:cosmoscrow";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Fees are expressed in basis points of the released amount
+const MAX_FEE_BPS: u16 = 10_000;
+
+// Events for off-chain indexers, emitted next to the per-method attributes. The event
+// types and attribute keys are part of the public interface and must stay stable.
+// Every event carries `escrow_id`. Amounts are comma-separated coins ("100ujuno,5uatom")
+// and `denom` lists the escrowed denoms in the same order ("ujuno,uatom"). `beneficiary`
+// lists all beneficiary addresses, comma-separated in the order given at creation.
+
+/// `escrow_created`: `escrow_id`, `creator`, `beneficiary`, `amount`, `denom`
+const EVENT_ESCROW_CREATED: &str = "escrow_created";
+/// `escrow_approved`: `escrow_id`, `approver`, `approval_count`, `required_approvals`
+const EVENT_ESCROW_APPROVED: &str = "escrow_approved";
+/// `escrow_released`: `escrow_id`, `beneficiary`, `amount` (the full escrowed amount),
+/// `denom`, and `fee` when a platform fee was taken out of `amount`
+const EVENT_ESCROW_RELEASED: &str = "escrow_released";
+/// `escrow_cancelled`: `escrow_id`, `creator` (refunded), `amount`, `denom`, and
+/// `reason` which is either `cancelled`, `expired` or `settled`. Settled escrows also
+/// carry `to_beneficiary`, and `amount` is what was refunded to the creator. Cancellations
+/// charged a fee carry `fee`, which is not part of `amount`.
+const EVENT_ESCROW_CANCELLED: &str = "escrow_cancelled";
+/// `emergency_withdraw_proposed`: `escrow_id`, `to`, `executable_at` (Unix seconds)
+const EVENT_EMERGENCY_WITHDRAW_PROPOSED: &str = "emergency_withdraw_proposed";
+/// `emergency_withdraw_executed`: `escrow_id`, `to`, `amount`, `denom`
+const EVENT_EMERGENCY_WITHDRAW_EXECUTED: &str = "emergency_withdraw_executed";
+
 #[entry_point]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
+    info: MessageInfo,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // The admin defaults to the instantiator
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender,
+    };
+
+    // Validate fee settings
+    let fee_bps = msg.fee_bps.unwrap_or(0);
+    if fee_bps > MAX_FEE_BPS {
+        return Err(ContractError::InvalidFee {});
+    }
+    let fee_recipient = msg
+        .fee_recipient
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    if fee_bps > 0 && fee_recipient.is_none() {
+        return Err(ContractError::InvalidFee {});
+    }
+    let cancel_fee_bps = msg.cancel_fee_bps.unwrap_or(0);
+    if cancel_fee_bps > MAX_FEE_BPS {
+        return Err(ContractError::InvalidFee {});
+    }
+
+    let approval_tiers = msg.approval_tiers.unwrap_or_default();
+    validate_approval_tiers(&approval_tiers)?;
+    validate_rate_limit(msg.rate_limit.as_ref())?;
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            admin: admin.clone(),
+            paused: false,
+            fee_recipient,
+            fee_bps,
+            cancel_fee_bps,
+            require_acceptance: msg.require_acceptance.unwrap_or(false),
+            max_description_length: msg
+                .max_description_length
+                .unwrap_or(DEFAULT_MAX_DESCRIPTION_LENGTH),
+            min_amounts: msg.min_amounts.unwrap_or_default(),
+            allowed_denoms: msg.allowed_denoms,
+            block_beneficiary_approver: msg.block_beneficiary_approver.unwrap_or(false),
+            two_phase_release: msg.two_phase_release.unwrap_or(false),
+            grace_period: msg.grace_period.unwrap_or(0),
+            creator_allowlist_enabled: msg.creator_allowlist.is_some(),
+            rate_limit: msg.rate_limit,
+            default_threshold_policy: msg.default_threshold_policy,
+            approval_tiers,
+            fee_rounding: msg.fee_rounding.unwrap_or_default(),
+        },
+    )?;
+    for creator in msg.creator_allowlist.unwrap_or_default() {
+        CREATOR_ALLOWLIST.save(deps.storage, &deps.api.addr_validate(&creator)?, &())?;
+    }
     
     // Initialize the escrow counter
     ESCROW_COUNTER.save(deps.storage, &0)?;
+    STATS.save(deps.storage, &Stats::default())?;
     
     Ok(Response::new()
         .add_attribute("method", "instantiate")
+        .add_attribute("admin", admin)
         .add_attribute("contract_name", CONTRACT_NAME)
         .add_attribute("contract_version", CONTRACT_VERSION))
 }
@@ -39,15 +126,96 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::CreateEscrow {
-            beneficiary,
-            approver1,
-            approver2,
-            approver3,
-            description,
-        } => execute_create_escrow(deps, env, info, beneficiary, approver1, approver2, approver3, description),
-        ExecuteMsg::ApproveRelease { escrow_id } => execute_approve_release(deps, env, info, escrow_id),
+        ExecuteMsg::CreateEscrow(msg) => execute_create_escrow(deps, env, info, msg),
+        ExecuteMsg::CreateUnfundedEscrow(msg) => execute_create_unfunded_escrow(deps, env, info, msg),
+        ExecuteMsg::FundEscrow { escrow_id } => execute_fund_escrow(deps, env, info, escrow_id),
+        ExecuteMsg::ApproveRelease { escrow_id, comment } => {
+            execute_approve_release(deps, env, info, escrow_id, comment)
+        }
+        ExecuteMsg::ApproveReleaseSigned { escrow_id, approver, signature } => {
+            execute_approve_release_signed(deps, env, info, escrow_id, approver, signature)
+        }
+        ExecuteMsg::SetApprovalKey { pubkey } => execute_set_approval_key(deps, info, pubkey),
+        ExecuteMsg::ApproveReleaseBatch { escrow_ids } => {
+            execute_approve_release_batch(deps, env, info, escrow_ids)
+        }
+        ExecuteMsg::SetApprovalDelegate { escrow_id, delegate } => {
+            execute_set_approval_delegate(deps, info, escrow_id, delegate)
+        }
+        ExecuteMsg::RevokeApprovalDelegate { escrow_id } => {
+            execute_revoke_approval_delegate(deps, info, escrow_id)
+        }
+        ExecuteMsg::RevokeApproval { escrow_id } => execute_revoke_approval(deps, env, info, escrow_id),
+        ExecuteMsg::Release { escrow_id } => execute_release(deps, env, info, escrow_id),
+        ExecuteMsg::FinalizeRelease { escrow_id } => execute_finalize_release(deps, env, info, escrow_id),
         ExecuteMsg::CancelEscrow { escrow_id } => execute_cancel_escrow(deps, env, info, escrow_id),
+        ExecuteMsg::CancelAllMyEscrows { start_after, limit } => {
+            execute_cancel_all_my_escrows(deps, env, info, start_after, limit)
+        }
+        ExecuteMsg::RefundExpired { escrow_id } => execute_refund_expired(deps, env, info, escrow_id),
+        ExecuteMsg::SweepExpired { limit } => execute_sweep_expired(deps, env, info, limit),
+        ExecuteMsg::ApproveCancel { escrow_id } => execute_approve_cancel(deps, env, info, escrow_id),
+        ExecuteMsg::AcceptEscrow { escrow_id } => execute_accept_escrow(deps, env, info, escrow_id),
+        ExecuteMsg::SetPayoutAddress { escrow_id, payout_address } => {
+            execute_set_payout_address(deps, env, info, escrow_id, payout_address)
+        }
+        ExecuteMsg::ReclaimUnaccepted { escrow_id } => {
+            execute_reclaim_unaccepted(deps, env, info, escrow_id)
+        }
+        ExecuteMsg::RaiseDispute { escrow_id } => execute_raise_dispute(deps, env, info, escrow_id),
+        ExecuteMsg::ResolveDispute { escrow_id, award_to_beneficiary } => {
+            execute_resolve_dispute(deps, env, info, escrow_id, award_to_beneficiary)
+        }
+        ExecuteMsg::SettleCancellation { escrow_id, to_beneficiary } => {
+            execute_settle_cancellation(deps, env, info, escrow_id, to_beneficiary)
+        }
+        ExecuteMsg::SaveTemplate { name, approvers, threshold, description } => {
+            execute_save_template(deps, info, name, approvers, threshold, description)
+        }
+        ExecuteMsg::CreateFromTemplate { name, beneficiary } => {
+            execute_create_from_template(deps, env, info, name, beneficiary)
+        }
+        ExecuteMsg::SetPaused { paused } => execute_set_paused(deps, info, paused),
+        ExecuteMsg::UpdateAllowedDenoms { allowed_denoms } => {
+            execute_update_allowed_denoms(deps, info, allowed_denoms)
+        }
+        ExecuteMsg::UpdateRateLimit { rate_limit } => execute_update_rate_limit(deps, info, rate_limit),
+        ExecuteMsg::UpdateDefaultThresholdPolicy { policy } => {
+            execute_update_default_threshold_policy(deps, info, policy)
+        }
+        ExecuteMsg::UpdateApprovalTiers { tiers } => execute_update_approval_tiers(deps, info, tiers),
+        ExecuteMsg::UpdateFeeRounding { rounding } => execute_update_fee_rounding(deps, info, rounding),
+        ExecuteMsg::UpdateCreatorAllowlist { enabled, add, remove } => {
+            execute_update_creator_allowlist(deps, info, enabled, add, remove)
+        }
+        ExecuteMsg::SetDenomAlias { denom, alias } => execute_set_denom_alias(deps, info, denom, alias),
+        ExecuteMsg::UpdateOwnership(action) => execute_update_ownership(deps, env, info, action),
+        ExecuteMsg::ProposeEmergencyWithdraw { escrow_id, to } => {
+            execute_propose_emergency_withdraw(deps, env, info, escrow_id, to)
+        }
+        ExecuteMsg::ExecuteEmergencyWithdraw { escrow_id } => {
+            execute_emergency_withdraw(deps, env, info, escrow_id)
+        }
+        ExecuteMsg::WithdrawFees { denom } => execute_withdraw_fees(deps, info, denom),
+        ExecuteMsg::TransferCreator { escrow_id, new_creator } => {
+            execute_transfer_creator(deps, env, info, escrow_id, new_creator)
+        }
+        ExecuteMsg::ReplaceApprover { escrow_id, old, new } => {
+            execute_replace_approver(deps, env, info, escrow_id, old, new)
+        }
+        ExecuteMsg::ProposeBeneficiaryChange { escrow_id, old_beneficiary, new_beneficiary } => {
+            execute_propose_beneficiary_change(deps, env, info, escrow_id, old_beneficiary, new_beneficiary)
+        }
+        ExecuteMsg::ApproveBeneficiaryChange { escrow_id } => {
+            execute_approve_beneficiary_change(deps, env, info, escrow_id)
+        }
+        ExecuteMsg::CancelBeneficiaryChange { escrow_id } => {
+            execute_cancel_beneficiary_change(deps, env, info, escrow_id)
+        }
+        ExecuteMsg::TopUp { escrow_id } => execute_top_up(deps, env, info, escrow_id),
+        ExecuteMsg::UpdateDescription { escrow_id, description } => {
+            execute_update_description(deps, env, info, escrow_id, description)
+        }
     }
 }
 
@@ -55,330 +223,3630 @@ pub fn execute_create_escrow(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    beneficiary: String,
-    approver1: String,
-    approver2: String,
-    approver3: Option<String>,
-    description: String,
+    msg: CreateEscrowMsg,
 ) -> Result<Response, ContractError> {
-    // Validate that exactly one coin was sent
-    if info.funds.len() != 1 {
-        return Err(ContractError::InsufficientFunds {});
+    create_escrow(deps, env, info, msg, true)
+}
+
+pub fn execute_create_unfunded_escrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CreateEscrowMsg,
+) -> Result<Response, ContractError> {
+    create_escrow(deps, env, info, msg, false)
+}
+
+/// Creates an escrow holding the sent funds, or an unfunded one to be funded later
+fn create_escrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CreateEscrowMsg,
+    funded: bool,
+) -> Result<Response, ContractError> {
+    let method = if funded { "create_escrow" } else { "create_unfunded_escrow" };
+    let CreateEscrowMsg {
+        beneficiaries,
+        approvers,
+        description,
+        threshold,
+        mode,
+        policy,
+        approval_ttl,
+        approver_weights,
+        required_weight,
+        expires_at,
+        release_after,
+        accept_deadline,
+        arbiter,
+        allow_duplicate_approvers,
+        idempotency_key,
+        metadata,
+        creator,
+        release_hook,
+        release_hook_msg,
+        remainder_policy,
+        depends_on,
+        expected_amount,
+        auto_approve_creator,
+    } = msg;
+
+    // A retry of an earlier creation hands back the sent funds and the existing escrow
+    if let Some(key) = idempotency_key.as_deref() {
+        if let Some(escrow_id) = IDEMPOTENCY_KEYS.may_load(deps.storage, (&info.sender, key))? {
+            let mut response = Response::new()
+                .add_attribute("method", method)
+                .add_attribute("escrow_id", escrow_id.to_string())
+                .add_attribute("duplicate", "true");
+            let refund: Vec<Coin> = info.funds.into_iter().filter(|coin| !coin.amount.is_zero()).collect();
+            if !refund.is_empty() {
+                response = response.add_message(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: refund,
+                });
+            }
+            return Ok(response);
+        }
     }
-    
-    let amount = info.funds[0].clone();
-    if amount.amount.is_zero() {
-        return Err(ContractError::InsufficientFunds {});
+
+    // New escrows cannot be created while the contract is paused
+    let config = CONFIG.load(deps.storage)?;
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    // In permissioned deployments only listed addresses can create escrows
+    if config.creator_allowlist_enabled && !CREATOR_ALLOWLIST.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {});
     }
 
-    // Validate addresses
-    let beneficiary_addr = deps.api.addr_validate(&beneficiary)?;
-    let approver1_addr = deps.api.addr_validate(&approver1)?;
-    let approver2_addr = deps.api.addr_validate(&approver2)?;
-    let approver3_addr = if let Some(addr) = approver3 {
-        Some(deps.api.addr_validate(&addr)?)
+    // Count this creation in the sender's current window, starting a new window once the
+    // previous one ended
+    let now = env.block.time.seconds();
+    let creation_count = match config.rate_limit.as_ref() {
+        Some(rate_limit) => {
+            let (window_start, count) = match CREATION_COUNTS.may_load(deps.storage, &info.sender)? {
+                Some((window_start, count)) if now < window_start.saturating_add(rate_limit.window) => {
+                    (window_start, count)
+                }
+                _ => (now, 0),
+            };
+            if count >= rate_limit.max_creations {
+                return Err(ContractError::RateLimited {});
+            }
+            Some((window_start, count + 1))
+        }
+        None => None,
+    };
+
+    // Unfunded escrows get their funds later through `FundEscrow`
+    let amount = if funded {
+        validate_escrow_funds(&config, &info.funds)?
+    } else if info.funds.iter().any(|coin| !coin.amount.is_zero()) {
+        return Err(ContractError::UnexpectedFunds {});
     } else {
-        None
+        vec![]
     };
+    // Unfunded escrows keep the expected amount for `FundEscrow` to check
+    if funded && !matches_expected_amount(&amount, expected_amount.as_ref()) {
+        return Err(ContractError::AmountMismatch {});
+    }
+
+    // Keep descriptions bounded, counting characters rather than bytes
+    if description.chars().count() > config.max_description_length as usize {
+        return Err(ContractError::DescriptionTooLong {});
+    }
 
-    // Note: We intentionally allow non-unique addresses between beneficiary and approvers
-    // to support flows where the beneficiary is also an approver.
+    // Metadata is opaque, only its size is bounded
+    if matches!(metadata.as_ref(), Some(metadata) if metadata.len() > MAX_METADATA_SIZE) {
+        return Err(ContractError::MetadataTooLarge {});
+    }
 
-    // Get next escrow ID
-    let escrow_id = ESCROW_COUNTER.update(deps.storage, |id| -> StdResult<u64> {
-        Ok(id + 1)
-    })?;
+    // Validate beneficiaries and their weights. The contract itself can't take part in an
+    // escrow, as funds sent to it would be trapped.
+    if beneficiaries.is_empty() || beneficiaries.len() > MAX_BENEFICIARIES {
+        return Err(ContractError::InvalidBeneficiary {});
+    }
+    let mut beneficiary_addrs: Vec<(Addr, u64)> = Vec::with_capacity(beneficiaries.len());
+    for (beneficiary, weight) in beneficiaries.iter() {
+        let addr = deps.api.addr_validate(beneficiary)?;
+        if *weight == 0
+            || addr == env.contract.address
+            || beneficiary_addrs.iter().any(|(existing, _)| *existing == addr)
+        {
+            return Err(ContractError::InvalidBeneficiary {});
+        }
+        beneficiary_addrs.push((addr, *weight));
+    }
+
+    // Validate approvers, rejecting duplicate addresses unless asked to drop them
+    if approvers.is_empty() {
+        return Err(ContractError::NoApprovers {});
+    }
+    if approvers.len() > MAX_APPROVERS {
+        return Err(ContractError::TooManyApprovers {});
+    }
+    // Weights are given per approver, a dropped duplicate loses its weight along with it
+    if matches!(approver_weights.as_ref(), Some(weights) if weights.len() != approvers.len() || weights.contains(&0)) {
+        return Err(ContractError::InvalidWeights {});
+    }
+    let mut approver_addrs: Vec<Addr> = Vec::with_capacity(approvers.len());
+    let mut weights: Vec<u64> = vec![];
+    for (index, approver) in approvers.iter().enumerate() {
+        let addr = deps.api.addr_validate(approver)?;
+        if addr == env.contract.address {
+            return Err(ContractError::InvalidApprover {});
+        }
+        if !approver_addrs.contains(&addr) {
+            approver_addrs.push(addr);
+            if let Some(approver_weights) = approver_weights.as_ref() {
+                weights.push(approver_weights[index]);
+            }
+        } else if !allow_duplicate_approvers.unwrap_or(false) {
+            return Err(ContractError::DuplicateApprover {});
+        }
+    }
+
+    // The sender funds the escrow, but may record someone else as its creator
+    let creator = match creator {
+        Some(creator) => deps.api.addr_validate(&creator)?,
+        None => info.sender.clone(),
+    };
+    if creator == env.contract.address {
+        return Err(ContractError::InvalidCreator {});
+    }
+
+    // The hook runs as this contract, so it cannot point back at it
+    let release_hook = match (release_hook, release_hook_msg.is_some()) {
+        (Some(hook), true) => {
+            let hook = deps.api.addr_validate(&hook)?;
+            if hook == env.contract.address {
+                return Err(ContractError::InvalidReleaseHook {});
+            }
+            Some(hook)
+        }
+        (None, false) => None,
+        _ => return Err(ContractError::InvalidReleaseHook {}),
+    };
+
+    // Only an existing escrow can be depended on. It was created earlier, so dependencies
+    // cannot form a cycle.
+    if let Some(depends_on) = depends_on {
+        if !ESCROWS.has(deps.storage, depends_on) {
+            return Err(ContractError::EscrowNotFound {});
+        }
+    }
+
+    // Validate arbiter
+    let arbiter = arbiter.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+
+    // Expiration, if any, must lie in the future
+    if let Some(expires_at) = expires_at {
+        if expires_at <= env.block.time.seconds() {
+            return Err(ContractError::InvalidExpiration {});
+        }
+    }
+
+    // The acceptance deadline, if any, must lie in the future
+    if let Some(accept_deadline) = accept_deadline {
+        if accept_deadline <= env.block.time.seconds() {
+            return Err(ContractError::InvalidExpiration {});
+        }
+    }
+
+    // A time lock ending after the expiration would never let the funds release
+    if let (Some(release_after), Some(expires_at)) = (release_after, expires_at) {
+        if release_after >= expires_at {
+            return Err(ContractError::InvalidExpiration {});
+        }
+    }
+
+    // Note: We intentionally allow non-unique addresses between beneficiaries and approvers
+    // to support flows where a beneficiary is also an approver, unless the config forbids it.
+    if config.block_beneficiary_approver
+        && beneficiary_addrs.iter().any(|(beneficiary, _)| approver_addrs.contains(beneficiary))
+    {
+        return Err(ContractError::BeneficiaryCannotApprove {});
+    }
 
     // Create the escrow
-    let escrow = Escrow {
-        id: escrow_id,
-        creator: info.sender.clone(),
-        beneficiary: beneficiary_addr.clone(),
+    let mut escrow = Escrow {
+        id: 0,
+        creator,
+        beneficiaries: beneficiary_addrs,
         amount: amount.clone(),
-        approver1: approver1_addr.clone(),
-        approver2: approver2_addr.clone(),
-        approver3: approver3_addr.clone(),
+        approvers: approver_addrs,
         description: description.clone(),
         approvals: vec![],
-        is_completed: false,
+        cancel_votes: vec![],
+        threshold,
+        status: EscrowStatus::Open,
         created_at: env.block.time.seconds(),
         completed_at: None,
+        expires_at,
+        release_after,
+        arbiter,
+        disputed: false,
+        accepted_by: vec![],
+        accept_deadline,
+        settlement: None,
+        beneficiary_change: None,
+        metadata,
+        release_eligible_at: None,
+        payout_overrides: vec![],
+        policy: None,
+        approval_ttl,
+        approver_weights: weights,
+        required_weight,
+        required_approvals: 0,
+        release_hook,
+        release_hook_msg,
+        remainder_policy: remainder_policy.unwrap_or_default(),
+        depends_on,
+        auto_approve_creator: false,
+        expected_amount,
+    };
+
+    // An auto-approved creator counts towards the threshold like any other approver
+    if auto_approve_creator.unwrap_or(false) && escrow.is_approver(&escrow.creator) {
+        escrow.auto_approve_creator = true;
+        escrow.approvals.push(Approval {
+            approver: escrow.creator.clone(),
+            approved_at: escrow.created_at,
+            comment: None,
+        });
+    }
+
+    // At least one approver other than the creator is needed for the escrow to ever release
+    let eligible_approvers = escrow.eligible_approvers();
+    if eligible_approvers == 0 || (escrow.auto_approve_creator && eligible_approvers == 1) {
+        return Err(ContractError::InvalidApprover {});
+    }
+
+    // An explicit threshold must be reachable by the approvers allowed to approve
+    if let Some(threshold) = threshold {
+        if threshold == 0 || threshold as usize > eligible_approvers {
+            return Err(ContractError::InvalidThreshold {});
+        }
+    }
+
+    // A declared policy fixes the number of approvers and sets the threshold itself
+    if let Some(policy) = policy {
+        if threshold.is_some() || mode.is_some() {
+            return Err(ContractError::PolicyMismatch {});
+        }
+        let approvers_match = match policy {
+            ApprovalPolicy::TwoOfThree => escrow.total_approvers() == 3,
+            ApprovalPolicy::Single => escrow.total_approvers() == 1,
+            ApprovalPolicy::Unanimous => true,
+            ApprovalPolicy::Custom(threshold) => threshold > 0,
+        };
+        escrow.policy = Some(policy);
+        if !approvers_match || escrow.rule_required_approvals() > eligible_approvers {
+            return Err(ContractError::PolicyMismatch {});
+        }
+    }
+
+    // A required weight replaces the approval count and must be reachable by the approvers
+    // allowed to approve
+    if let Some(required_weight) = required_weight {
+        if threshold.is_some() || mode.is_some() || escrow.policy.is_some() {
+            return Err(ContractError::InvalidWeights {});
+        }
+        if required_weight == 0 || required_weight > escrow.eligible_weight() {
+            return Err(ContractError::InvalidWeights {});
+        }
+    }
+
+    // Escrows without any explicit requirement get the contract-wide default, fixed now so
+    // later changes to the default leave them alone
+    let mode = match mode {
+        None if threshold.is_none() && escrow.policy.is_none() && required_weight.is_none() => {
+            config.default_threshold_policy.clone()
+        }
+        mode => mode,
     };
 
+    // Resolve the approval mode into a fixed threshold
+    if let Some(mode) = mode {
+        if threshold.is_some() {
+            return Err(ContractError::InvalidThreshold {});
+        }
+        escrow.threshold = Some(escrow.mode_required_approvals(&mode) as u32);
+    }
+
+    // Freeze the requirement so later changes cannot move it under the parties
+    escrow.required_approvals = escrow.rule_required_approvals() as u32;
+    apply_approval_tiers(&config, &mut escrow);
+
+    // An escrow that needs no approvals would release without anyone signing off
+    if escrow.required_approvals() == 0 {
+        return Err(ContractError::NoApprovers {});
+    }
+
+    // The creator's own approval must not be enough to release
+    if escrow.can_be_released(escrow.created_at) {
+        return Err(ContractError::InvalidThreshold {});
+    }
+
+    // Get next escrow ID
+    let escrow_id = ESCROW_COUNTER.update(deps.storage, |id| -> Result<u64, ContractError> {
+        id.checked_add(1).ok_or(ContractError::CounterOverflow {})
+    })?;
+    escrow.id = escrow_id;
+
     // Save the escrow
     ESCROWS.save(deps.storage, escrow_id, &escrow)?;
 
     // Update indexes
-    update_escrow_indexes(deps.storage, &escrow, true)?;
+    update_escrow_indexes(deps.storage, &escrow)?;
+    ESCROWS_BY_CREATED_AT.save(deps.storage, (escrow.created_at, escrow_id), &())?;
+    if let Some(expires_at) = escrow.expires_at {
+        ESCROWS_BY_EXPIRES_AT.save(deps.storage, (expires_at, escrow_id), &())?;
+    }
+    if let Some(key) = idempotency_key.as_deref() {
+        IDEMPOTENCY_KEYS.save(deps.storage, (&info.sender, key), &escrow_id)?;
+    }
+    if let Some(creation_count) = creation_count {
+        CREATION_COUNTS.save(deps.storage, &info.sender, &creation_count)?;
+    }
+    record_escrow_opened(deps.storage, &escrow.amount)?;
+    record_history(deps.storage, escrow_id, EscrowEventKind::Created, &info.sender, escrow.created_at)?;
 
     Ok(Response::new()
-        .add_attribute("method", "create_escrow")
+        .add_attribute("method", method)
         .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("creator", info.sender)
-        .add_attribute("beneficiary", beneficiary)
-        .add_attribute("amount", amount.to_string())
-        .add_attribute("description", description))
+        .add_attribute("creator", escrow.creator.to_string())
+        .add_attribute("beneficiary", beneficiaries_to_string(&escrow))
+        .add_attribute("amount", coins_to_string(&amount))
+        .add_attribute("description", description)
+        .add_event(escrow_event(EVENT_ESCROW_CREATED, &escrow)
+            .add_attribute("creator", escrow.creator.to_string())
+            .add_attribute("beneficiary", beneficiaries_to_string(&escrow))
+            .add_attribute("amount", coins_to_string(&escrow.amount))
+            .add_attribute("denom", denoms_to_string(&escrow.amount))))
 }
 
-pub fn execute_approve_release(
+/// Checks the funds sent to create or fund an escrow and returns the non-zero coins
+fn validate_escrow_funds(config: &Config, funds: &[Coin]) -> Result<Vec<Coin>, ContractError> {
+    // Validate that a non-zero amount of at least one coin was sent
+    let amount: Vec<Coin> = funds
+        .iter()
+        .filter(|coin| !coin.amount.is_zero())
+        .cloned()
+        .collect();
+    if amount.is_empty() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    // Only whitelisted denoms can be escrowed, if a whitelist is set
+    if let Some(allowed_denoms) = config.allowed_denoms.as_ref() {
+        if amount.iter().any(|coin| !allowed_denoms.contains(&coin.denom)) {
+            return Err(ContractError::DenomNotAllowed {});
+        }
+    }
+
+    // Reject dust escrows in denoms that have a configured minimum
+    for coin in amount.iter() {
+        let below_minimum = config
+            .min_amounts
+            .iter()
+            .any(|min| min.denom == coin.denom && coin.amount < min.amount);
+        if below_minimum {
+            return Err(ContractError::BelowMinimum {});
+        }
+    }
+
+    Ok(amount)
+}
+
+pub fn execute_fund_escrow(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     escrow_id: u64,
 ) -> Result<Response, ContractError> {
-    let mut escrow = ESCROWS.load(deps.storage, escrow_id)?;
-    
-    if escrow.is_completed {
-        return Err(ContractError::EscrowCompleted {});
-    }
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
 
-    // Check if sender is an approver
-    if !escrow.is_approver(&info.sender) {
+    // Only the creator can fund the escrow
+    if info.sender != escrow.creator {
         return Err(ContractError::Unauthorized {});
     }
 
-    // Check if already approved
-    if escrow.has_approved(&info.sender) {
-        return Err(ContractError::AlreadyApproved {});
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
     }
 
-    // Creator is allowed to approve if they are one of the approvers
+    if escrow.is_funded() {
+        return Err(ContractError::AlreadyFunded {});
+    }
 
-    // Add approval
-    escrow.approvals.push(info.sender.clone());
+    // Funding activates the escrow, which is not possible while the contract is paused
+    let config = CONFIG.load(deps.storage)?;
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
 
-    let mut response = Response::new()
-        .add_attribute("method", "approve_release")
-        .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("approver", info.sender.to_string())
-        .add_attribute("total_approvals", escrow.approvals.len().to_string());
+    escrow.amount = validate_escrow_funds(&config, &info.funds)?;
+    if !matches_expected_amount(&escrow.amount, escrow.expected_amount.as_ref()) {
+        return Err(ContractError::AmountMismatch {});
+    }
+    apply_approval_tiers(&config, &mut escrow);
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    for coin in escrow.amount.iter() {
+        ESCROWS_BY_DENOM.save(deps.storage, (&coin.denom, escrow_id), &())?;
+    }
+    lock_funds(deps.storage, &escrow.amount)?;
+    record_history(deps.storage, escrow_id, EscrowEventKind::Funded, &info.sender, env.block.time.seconds())?;
 
-    // Check if we have enough approvals to release funds
-    if escrow.can_be_released() {
-        // Mark as completed
-        escrow.is_completed = true;
-        escrow.completed_at = Some(env.block.time.seconds());
+    Ok(Response::new()
+        .add_attribute("method", "fund_escrow")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("amount", coins_to_string(&escrow.amount)))
+}
 
-        // Add bank message to send funds to beneficiary
-        let bank_msg = BankMsg::Send {
-            to_address: escrow.beneficiary.to_string(),
-            amount: vec![escrow.amount.clone()],
-        };
+pub fn execute_approve_release(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    comment: Option<String>,
+) -> Result<Response, ContractError> {
+    let escrow = load_escrow(deps.storage, escrow_id)?;
 
-        response = response
-            .add_message(bank_msg)
-            .add_attribute("released", "true")
-            .add_attribute("released_to", escrow.beneficiary.to_string())
-            .add_attribute("amount_released", escrow.amount.to_string());
-    }
+    // Check if sender is an approver, or approves on an approver's behalf
+    let approver = if escrow.is_approver(&info.sender) {
+        info.sender.clone()
+    } else {
+        delegating_approver(deps.storage, &escrow, &info.sender)?.ok_or(ContractError::NotAnApprover {})?
+    };
 
-    // Save updated escrow
-    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    let response = Response::new()
+        .add_attribute("method", "approve_release")
+        .add_attribute("escrow_id", escrow_id.to_string());
 
-    Ok(response)
+    approve(deps, env, escrow, approver, (&info.sender, "delegate"), comment, response)
 }
 
-pub fn execute_cancel_escrow(
+pub fn execute_approve_release_signed(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     escrow_id: u64,
+    approver: String,
+    signature: Binary,
 ) -> Result<Response, ContractError> {
-    let mut escrow = ESCROWS.load(deps.storage, escrow_id)?;
-    
-    // Only creator can cancel
-    if escrow.creator != info.sender {
-        return Err(ContractError::Unauthorized {});
+    let escrow = load_escrow(deps.storage, escrow_id)?;
+    let approver = deps.api.addr_validate(&approver)?;
+    if !escrow.is_approver(&approver) {
+        return Err(ContractError::NotAnApprover {});
     }
 
-    if escrow.is_completed {
-        return Err(ContractError::EscrowCompleted {});
+    // The signed message names the escrow and this contract, so a signature cannot be
+    // replayed on another escrow or another deployment. It also names the approver's nonce,
+    // which every approval and revocation moves on, so it cannot be replayed later either.
+    let pubkey = APPROVAL_KEYS.may_load(deps.storage, &approver)?.ok_or(ContractError::InvalidSignature {})?;
+    let nonce = APPROVAL_NONCES.may_load(deps.storage, (escrow_id, &approver))?.unwrap_or_default();
+    let hash = Sha256::digest(approval_sign_bytes(&env.contract.address, escrow_id, &approver, nonce));
+    let valid = deps.api.secp256k1_verify(&hash, &signature, &pubkey).unwrap_or(false);
+    if !valid {
+        return Err(ContractError::InvalidSignature {});
     }
 
-    // Can only cancel if no approvals yet
-    if !escrow.approvals.is_empty() {
-        return Err(ContractError::Unauthorized {});
+    let response = Response::new()
+        .add_attribute("method", "approve_release_signed")
+        .add_attribute("escrow_id", escrow_id.to_string());
+    let actor = (&info.sender, "relayer");
+    approve(deps, env, escrow, approver, actor, None, response)
+}
+
+/// Moves the approver's nonce on, invalidating any signed approval not submitted yet
+fn bump_approval_nonce(storage: &mut dyn Storage, escrow_id: u64, approver: &Addr) -> StdResult<()> {
+    APPROVAL_NONCES.update(storage, (escrow_id, approver), |nonce| -> StdResult<_> {
+        Ok(nonce.unwrap_or_default() + 1)
+    })?;
+    Ok(())
+}
+
+pub fn execute_set_approval_key(
+    deps: DepsMut,
+    info: MessageInfo,
+    pubkey: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let response = Response::new()
+        .add_attribute("method", "set_approval_key")
+        .add_attribute("owner", info.sender.to_string());
+
+    match pubkey {
+        Some(pubkey) => {
+            // Compressed or uncompressed SEC1 encoding
+            if !matches!(pubkey.len(), 33 | 65) {
+                return Err(ContractError::InvalidPublicKey {});
+            }
+            APPROVAL_KEYS.save(deps.storage, &info.sender, &pubkey)?;
+            Ok(response.add_attribute("pubkey", pubkey.to_base64()))
+        }
+        None => {
+            APPROVAL_KEYS.remove(deps.storage, &info.sender);
+            Ok(response.add_attribute("pubkey", "none"))
+        }
     }
+}
 
-    // Mark as completed
-    escrow.is_completed = true;
+/// Records `approver`'s approval, releasing the escrow if that makes it releasable. `actor`
+/// is the sender and the attribute naming it when it approves on the approver's behalf.
+fn approve(
+    deps: DepsMut,
+    env: Env,
+    mut escrow: Escrow,
+    approver: Addr,
+    (actor, actor_attribute): (&Addr, &str),
+    comment: Option<String>,
+    mut response: Response,
+) -> Result<Response, ContractError> {
+    let escrow_id = escrow.id;
+    let now = env.block.time.seconds();
+    check_can_approve(&escrow, &approver, now)?;
 
-    // Return funds to creator
-    let bank_msg = BankMsg::Send {
-        to_address: escrow.creator.to_string(),
-        amount: vec![escrow.amount.clone()],
-    };
+    if matches!(comment.as_ref(), Some(comment) if comment.chars().count() > MAX_COMMENT_LENGTH) {
+        return Err(ContractError::CommentTooLong {});
+    }
 
-    // Update indexes
-    update_escrow_indexes(deps.storage, &escrow, false)?;
+    // Add approval
+    add_approval(&mut escrow, &approver, now, comment);
 
-    // Save updated escrow
-    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    // Check if we have enough approvals to release funds, running every check the release
+    // makes before writing anything so a failed approval leaves no trace
+    let config = CONFIG.load(deps.storage)?;
+    let ready = escrow.can_be_released(now);
+    let releases = releases_on_approval(deps.as_ref(), &env, &config, &escrow)?;
+    record_history(deps.storage, escrow_id, EscrowEventKind::Approved, actor, now)?;
+    bump_approval_nonce(deps.storage, escrow_id, &approver)?;
 
-    Ok(Response::new()
-        .add_message(bank_msg)
-        .add_attribute("method", "cancel_escrow")
-        .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("refunded_to", escrow.creator.to_string()))
-}
+    response = response
+        .add_attribute("approver", approver.to_string())
+        .add_attribute("total_approvals", escrow.approvals.len().to_string());
+    if approver != *actor {
+        response = response.add_attribute(actor_attribute, actor.to_string());
+    }
+    response = response
+        .add_event(escrow_event(EVENT_ESCROW_APPROVED, &escrow)
+            .add_attribute("approver", approver.to_string())
+            .add_attribute("approval_count", escrow.approvals.len().to_string())
+            .add_attribute("required_approvals", escrow.required_approvals().to_string()));
 
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetEscrow { escrow_id } => to_json_binary(&query_escrow(deps, escrow_id)?),
-        QueryMsg::GetEscrowsByAddress { address, start_after, limit } => {
-            to_json_binary(&query_escrows_by_address(deps, address, start_after, limit)?)
-        }
-        QueryMsg::GetAllEscrows { start_after, limit } => {
-            to_json_binary(&query_all_escrows(deps, start_after, limit)?)
+    if config.two_phase_release && ready {
+        // Start the grace period in which a dispute can still stop the release
+        if config.grace_period > 0 && escrow.release_eligible_at.is_none() {
+            let release_eligible_at = now + config.grace_period;
+            escrow.release_eligible_at = Some(release_eligible_at);
+            response = response.add_attribute("release_eligible_at", release_eligible_at.to_string());
         }
+        response = response.add_attribute("ready_to_release", "true");
+        ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    } else if releases {
+        response = release_escrow(deps, &env, &mut escrow, actor, response)?;
+    } else {
+        ESCROWS.save(deps.storage, escrow_id, &escrow)?;
     }
-}
 
-fn query_escrow(deps: Deps, escrow_id: u64) -> StdResult<EscrowResponse> {
-    let escrow = ESCROWS.load(deps.storage, escrow_id)?;
-    Ok(escrow_to_response(escrow))
+    Ok(response)
 }
 
-fn query_escrows_by_address(
-    deps: Deps,
-    address: String,
-    start_after: Option<u64>,
-    limit: Option<u32>,
-) -> StdResult<EscrowListResponse> {
-    let addr = deps.api.addr_validate(&address)?;
-    let limit = limit.unwrap_or(10) as usize;
-    let start = start_after.unwrap_or(0);
+/// Fails if `approver` could not approve the escrow right now
+fn check_can_approve(escrow: &Escrow, approver: &Addr, now: u64) -> Result<(), ContractError> {
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
 
-    let mut escrow_ids = vec![];
-    
-    // Get escrows where address is creator
-    if let Ok(creator_escrows) = ESCROWS_BY_CREATOR.load(deps.storage, &addr) {
-        escrow_ids.extend(creator_escrows);
+    if escrow.is_expired(now) {
+        return Err(ContractError::EscrowExpired {});
     }
-    
-    // Get escrows where address is beneficiary
-    if let Ok(beneficiary_escrows) = ESCROWS_BY_BENEFICIARY.load(deps.storage, &addr) {
-        escrow_ids.extend(beneficiary_escrows);
+
+    // Disputed escrows can only be settled by the arbiter
+    if escrow.disputed {
+        return Err(ContractError::Disputed {});
     }
-    
-    // Get escrows where address is approver
-    if let Ok(approver_escrows) = ESCROWS_BY_APPROVER.load(deps.storage, &addr) {
-        escrow_ids.extend(approver_escrows);
+
+    if !escrow.is_funded() {
+        return Err(ContractError::NotFunded {});
     }
 
-    // Remove duplicates and sort
-    escrow_ids.sort();
-    escrow_ids.dedup();
+    // Check if already approved. An approval older than the approval TTL is renewed.
+    if escrow.has_current_approval(approver, now) {
+        return Err(ContractError::AlreadyApproved {});
+    }
 
-    // Apply pagination
-    let filtered_ids: Vec<u64> = escrow_ids
-        .into_iter()
-        .filter(|&id| id > start)
-        .take(limit)
-        .collect();
+    // The creator cannot approve their own escrow, even when listed as an approver, unless
+    // they were auto-approved at creation and renew or restore that approval
+    if !escrow.is_eligible(approver) {
+        return Err(ContractError::CannotSelfApprove {});
+    }
 
-    let mut escrows = vec![];
-    for id in filtered_ids {
-        if let Ok(escrow) = ESCROWS.load(deps.storage, id) {
-            escrows.push(escrow_to_response(escrow));
+    Ok(())
+}
+
+/// Records an approval by `approver`, replacing any earlier one
+fn add_approval(escrow: &mut Escrow, approver: &Addr, now: u64, comment: Option<String>) {
+    escrow.approvals.retain(|approval| approval.approver != approver);
+    escrow.approvals.push(Approval {
+        approver: approver.clone(),
+        approved_at: now,
+        comment,
+    });
+}
+
+/// Whether the approvals on `escrow` release it right away, failing if the release itself
+/// would fail. Time-locked escrows, escrows with a pending beneficiary change and escrows
+/// waiting for their dependency keep the approvals and are released later through
+/// `Release`, and with two-phase release a party to the escrow has to finalize it.
+fn releases_on_approval(deps: Deps, env: &Env, config: &Config, escrow: &Escrow) -> Result<bool, ContractError> {
+    let now = env.block.time.seconds();
+    let held_back = escrow.beneficiary_change.is_some() || !dependency_released(deps.storage, escrow)?;
+    if config.two_phase_release || !escrow.can_be_released(now) || escrow.is_time_locked(now) || held_back {
+        return Ok(false);
+    }
+
+    // The beneficiaries may have to accept the escrow first
+    if config.require_acceptance && !escrow.is_accepted() {
+        return Err(ContractError::NotAccepted {});
+    }
+    check_releasable(deps, env, escrow)?;
+    Ok(true)
+}
+
+/// Whether the funds are exactly the expected amount, if there is one
+fn matches_expected_amount(amount: &[Coin], expected_amount: Option<&Coin>) -> bool {
+    match expected_amount {
+        Some(expected) => amount == [expected.clone()],
+        None => true,
+    }
+}
+
+/// Whether the escrow this one depends on, if any, was released
+fn dependency_released(storage: &dyn Storage, escrow: &Escrow) -> StdResult<bool> {
+    match escrow.depends_on {
+        Some(depends_on) => Ok(ESCROWS.load(storage, depends_on)?.status == EscrowStatus::Released),
+        None => Ok(true),
+    }
+}
+
+/// Finds the approver that made `delegate` their approval delegate, preferring one that
+/// has not approved yet
+fn delegating_approver(storage: &dyn Storage, escrow: &Escrow, delegate: &Addr) -> StdResult<Option<Addr>> {
+    let mut delegating = vec![];
+    for approver in escrow.approvers.iter() {
+        if APPROVAL_DELEGATES.may_load(storage, (escrow.id, approver))?.as_ref() == Some(delegate) {
+            delegating.push(approver);
+        }
+    }
+
+    let approver = delegating
+        .iter()
+        .find(|approver| !escrow.has_approved(approver))
+        .or(delegating.first());
+    Ok(approver.map(|approver| (*approver).clone()))
+}
+
+pub fn execute_set_approval_delegate(
+    deps: DepsMut,
+    info: MessageInfo,
+    escrow_id: u64,
+    delegate: String,
+) -> Result<Response, ContractError> {
+    let escrow = load_escrow(deps.storage, escrow_id)?;
+
+    if !escrow.is_approver(&info.sender) {
+        return Err(ContractError::NotAnApprover {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    let delegate = deps.api.addr_validate(&delegate)?;
+    APPROVAL_DELEGATES.save(deps.storage, (escrow_id, &info.sender), &delegate)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_approval_delegate")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("approver", info.sender.to_string())
+        .add_attribute("delegate", delegate))
+}
+
+pub fn execute_revoke_approval_delegate(
+    deps: DepsMut,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    if !APPROVAL_DELEGATES.has(deps.storage, (escrow_id, &info.sender)) {
+        return Err(ContractError::NoDelegate {});
+    }
+    APPROVAL_DELEGATES.remove(deps.storage, (escrow_id, &info.sender));
+
+    Ok(Response::new()
+        .add_attribute("method", "revoke_approval_delegate")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("approver", info.sender.to_string()))
+}
+
+pub fn execute_approve_release_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    if escrow_ids.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::BatchTooLarge {});
+    }
+
+    let mut response = Response::new().add_attribute("method", "approve_release_batch");
+    let mut approved = vec![];
+    let mut skipped = vec![];
+
+    // `approve` only writes to storage once all its checks, including those of the release,
+    // passed, so a failed approval leaves no partial state behind and can simply be skipped
+    for escrow_id in escrow_ids {
+        match execute_approve_release(deps.branch(), env.clone(), info.clone(), escrow_id, None) {
+            Ok(res) => {
+                response = response.add_submessages(res.messages).add_events(res.events);
+                approved.push(escrow_id.to_string());
+            }
+            Err(_) => skipped.push(escrow_id.to_string()),
+        }
+    }
+
+    Ok(response
+        .add_attribute("approver", info.sender)
+        .add_attribute("approved", approved.join(","))
+        .add_attribute("skipped", skipped.join(",")))
+}
+
+pub fn execute_revoke_approval(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Released escrows are final. Open escrows that reached the threshold are waiting for
+    // their time lock or for the release to be finalized, and can still be revoked.
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    if !escrow.has_approved(&info.sender) {
+        return Err(ContractError::NotApproved {});
+    }
+
+    escrow.approvals.retain(|approval| approval.approver != info.sender);
+    if !escrow.can_be_released(env.block.time.seconds()) {
+        escrow.release_eligible_at = None;
+    }
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    record_history(deps.storage, escrow_id, EscrowEventKind::ApprovalRevoked, &info.sender, env.block.time.seconds())?;
+    bump_approval_nonce(deps.storage, escrow_id, &info.sender)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "revoke_approval")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("approver", info.sender)
+        .add_attribute("total_approvals", escrow.approvals.len().to_string()))
+}
+
+pub fn execute_release(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    // With two-phase release only a party to the escrow can pay it out
+    if CONFIG.load(deps.storage)?.two_phase_release {
+        return execute_finalize_release(deps, env, info, escrow_id);
+    }
+
+    let escrow = load_escrow(deps.storage, escrow_id)?;
+    let response = Response::new()
+        .add_attribute("method", "release")
+        .add_attribute("escrow_id", escrow_id.to_string());
+
+    release_approved_escrow(deps, env, escrow, &info.sender, response)
+}
+
+pub fn execute_finalize_release(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Once a grace period is running, nobody can finalize before it ends and anyone can after
+    match escrow.release_eligible_at {
+        Some(release_eligible_at) => {
+            if env.block.time.seconds() < release_eligible_at {
+                return Err(ContractError::TooEarly {});
+            }
+        }
+        None => {
+            if info.sender != escrow.creator && !escrow.is_approver(&info.sender) {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
+    }
+
+    let response = Response::new()
+        .add_attribute("method", "finalize_release")
+        .add_attribute("escrow_id", escrow_id.to_string());
+
+    release_approved_escrow(deps, env, escrow, &info.sender, response)
+}
+
+/// Releases an escrow that reached its approval threshold, once nothing else holds it back
+fn release_approved_escrow(
+    deps: DepsMut,
+    env: Env,
+    mut escrow: Escrow,
+    actor: &Addr,
+    response: Response,
+) -> Result<Response, ContractError> {
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    if escrow.disputed {
+        return Err(ContractError::Disputed {});
+    }
+
+    // The approvers must have agreed and the time lock must have ended
+    if !escrow.can_be_released(env.block.time.seconds()) {
+        return Err(ContractError::ConditionsNotMet {});
+    }
+
+    if escrow.is_time_locked(env.block.time.seconds()) {
+        return Err(ContractError::TooEarly {});
+    }
+
+    if CONFIG.load(deps.storage)?.require_acceptance && !escrow.is_accepted() {
+        return Err(ContractError::NotAccepted {});
+    }
+
+    release_escrow(deps, &env, &mut escrow, actor, response)
+}
+
+pub fn execute_cancel_escrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+    if let Some((_, err)) = cancel_blocker(&escrow, &info.sender) {
+        return Err(err);
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let (bank_msgs, event, fee) = cancel_by_creator(deps.storage, &config, &mut escrow, &info.sender, now)?;
+
+    let response = Response::new()
+        .add_messages(bank_msgs)
+        .add_event(event)
+        .add_attribute("method", "cancel_escrow")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("refunded_to", escrow.creator.to_string());
+    match config.fee_recipient {
+        Some(fee_recipient) if !fee.is_empty() => Ok(response
+            .add_attribute("fee_recipient", fee_recipient.to_string())
+            .add_attribute("fee", coins_to_string(&fee))),
+        _ => Ok(response),
+    }
+}
+
+/// Why `sender` cannot cancel the escrow on their own, as a reason for `CanCancel` and the
+/// error `CancelEscrow` fails with. `None` when the cancellation would go through.
+fn cancel_blocker(escrow: &Escrow, sender: &Addr) -> Option<(&'static str, ContractError)> {
+    // Only creator can cancel
+    if escrow.creator != *sender {
+        return Some(("only the creator can cancel", ContractError::Unauthorized {}));
+    }
+
+    if escrow.is_completed() {
+        return Some(("escrow is completed", ContractError::EscrowCompleted {}));
+    }
+
+    if escrow.disputed {
+        return Some(("escrow is disputed", ContractError::Disputed {}));
+    }
+
+    // Can only cancel unilaterally if no approvals yet, otherwise approvers must vote to cancel
+    if escrow.has_outside_approvals() {
+        return Some(("escrow has approvals, approvers must vote to cancel", ContractError::Unauthorized {}));
+    }
+
+    None
+}
+
+pub fn execute_cancel_all_my_escrows(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = (limit.unwrap_or(10) as usize).min(MAX_BATCH_SIZE);
+    let config = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+
+    // Every escrow looked at counts towards the limit. The creator index also holds
+    // completed escrows, which are passed over, and open escrows that can no longer be
+    // cancelled by the creator alone are reported as skipped.
+    let escrow_ids = ESCROWS_BY_CREATOR
+        .prefix(&info.sender)
+        .keys(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_checked = escrow_ids.last().map(|escrow_id| escrow_id.to_string()).unwrap_or_default();
+
+    let mut response = Response::new().add_attribute("method", "cancel_all_my_escrows");
+    let mut cancelled = vec![];
+    let mut skipped = vec![];
+    for escrow_id in escrow_ids {
+        let mut escrow = ESCROWS.load(deps.storage, escrow_id)?;
+        if escrow.is_completed() {
+            continue;
+        }
+        if escrow.disputed || escrow.has_outside_approvals() {
+            skipped.push(escrow_id.to_string());
+            continue;
+        }
+        let (bank_msgs, event, _) = cancel_by_creator(deps.storage, &config, &mut escrow, &info.sender, now)?;
+        response = response.add_messages(bank_msgs).add_event(event);
+        cancelled.push(escrow_id.to_string());
+    }
+
+    Ok(response
+        .add_attribute("cancelled", cancelled.len().to_string())
+        .add_attribute("escrow_ids", cancelled.join(","))
+        .add_attribute("skipped", skipped.join(","))
+        .add_attribute("last_checked", last_checked))
+}
+
+pub fn execute_reclaim_unaccepted(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Only creator can reclaim
+    if escrow.creator != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    if escrow.disputed {
+        return Err(ContractError::Disputed {});
+    }
+
+    if escrow.is_accepted() {
+        return Err(ContractError::AlreadyAccepted {});
+    }
+
+    // Escrows without an acceptance deadline can't be reclaimed this way
+    if !escrow.is_acceptance_overdue(env.block.time.seconds()) {
+        return Err(ContractError::TooEarly {});
+    }
+
+    let (bank_msgs, event) = refund_escrow(
+        deps.storage,
+        &mut escrow,
+        EscrowStatus::Cancelled,
+        &info.sender,
+        env.block.time.seconds(),
+    )?;
+
+    Ok(Response::new()
+        .add_messages(bank_msgs)
+        .add_event(event)
+        .add_attribute("method", "reclaim_unaccepted")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("refunded_to", escrow.creator.to_string()))
+}
+
+pub fn execute_refund_expired(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    if escrow.disputed {
+        return Err(ContractError::Disputed {});
+    }
+
+    // Anyone can trigger the refund, but only once the escrow has expired
+    if !escrow.is_expired(env.block.time.seconds()) {
+        return Err(ContractError::EscrowNotExpired {});
+    }
+
+    let (bank_msgs, event) = refund_escrow(
+        deps.storage,
+        &mut escrow,
+        EscrowStatus::Expired,
+        &info.sender,
+        env.block.time.seconds(),
+    )?;
+
+    Ok(Response::new()
+        .add_messages(bank_msgs)
+        .add_event(event)
+        .add_attribute("method", "refund_expired")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("refunded_to", escrow.creator.to_string()))
+}
+
+pub fn execute_sweep_expired(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = (limit.unwrap_or(10) as usize).min(MAX_BATCH_SIZE);
+    let now = env.block.time.seconds();
+
+    // Only undisputed open escrows that expired before now are in this part of the index,
+    // and swept escrows leave it, so the next call picks up where this one stopped
+    let expired = ESCROWS_BY_EXPIRES_AT
+        .keys(deps.storage, None, Some(Bound::exclusive((now, 0))), Order::Ascending)
+        .take(limit)
+        .map(|key| {
+            let (_, escrow_id) = key?;
+            Ok((escrow_id, ESCROWS.load(deps.storage, escrow_id)?))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut response = Response::new().add_attribute("method", "sweep_expired");
+    let mut swept = vec![];
+    for (escrow_id, mut escrow) in expired {
+        let (bank_msgs, event) =
+            refund_escrow(deps.storage, &mut escrow, EscrowStatus::Expired, &info.sender, now)?;
+        response = response.add_messages(bank_msgs).add_event(event);
+        swept.push(escrow_id.to_string());
+    }
+
+    Ok(response
+        .add_attribute("swept", swept.len().to_string())
+        .add_attribute("escrow_ids", swept.join(",")))
+}
+
+pub fn execute_approve_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    if escrow.disputed {
+        return Err(ContractError::Disputed {});
+    }
+
+    // Cancel votes follow the same rules as release approvals
+    if !escrow.is_approver(&info.sender) {
+        return Err(ContractError::NotAnApprover {});
+    }
+
+    if escrow.has_voted_cancel(&info.sender) {
+        return Err(ContractError::AlreadyApproved {});
+    }
+
+    if info.sender == escrow.creator {
+        return Err(ContractError::CannotSelfApprove {});
+    }
+
+    escrow.cancel_votes.push(info.sender.clone());
+    record_history(deps.storage, escrow_id, EscrowEventKind::CancelVoted, &info.sender, env.block.time.seconds())?;
+
+    let mut response = Response::new()
+        .add_attribute("method", "approve_cancel")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("approver", info.sender.to_string())
+        .add_attribute("total_cancel_votes", escrow.cancel_votes.len().to_string());
+
+    // Refund the creator once enough approvers agreed to cancel
+    if escrow.can_be_cancelled() {
+        let (bank_msgs, event) = refund_escrow(
+            deps.storage,
+            &mut escrow,
+            EscrowStatus::Cancelled,
+            &info.sender,
+            env.block.time.seconds(),
+        )?;
+        response = response
+            .add_messages(bank_msgs)
+            .add_event(event)
+            .add_attribute("cancelled", "true")
+            .add_attribute("refunded_to", escrow.creator.to_string());
+    } else {
+        ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    }
+
+    Ok(response)
+}
+
+pub fn execute_accept_escrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Only beneficiaries can accept
+    if !escrow.is_beneficiary(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    if !escrow.accepted_by.contains(&info.sender) {
+        escrow.accepted_by.push(info.sender.clone());
+        record_history(deps.storage, escrow_id, EscrowEventKind::Accepted, &info.sender, env.block.time.seconds())?;
+    }
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "accept_escrow")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("beneficiary", info.sender)
+        .add_attribute("accepted", escrow.is_accepted().to_string()))
+}
+
+pub fn execute_set_payout_address(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    payout_address: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Only beneficiaries can redirect their share
+    if !escrow.is_beneficiary(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    let payout_address = deps.api.addr_validate(&payout_address)?;
+    if payout_address == env.contract.address {
+        return Err(ContractError::InvalidBeneficiary {});
+    }
+
+    escrow.payout_overrides.retain(|(beneficiary, _)| *beneficiary != info.sender);
+    if payout_address != info.sender {
+        escrow.payout_overrides.push((info.sender.clone(), payout_address.clone()));
+    }
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    record_history(deps.storage, escrow_id, EscrowEventKind::PayoutAddressSet, &info.sender, env.block.time.seconds())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_payout_address")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("beneficiary", info.sender)
+        .add_attribute("payout_address", payout_address))
+}
+
+pub fn execute_raise_dispute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Only the creator or a beneficiary can raise a dispute
+    if info.sender != escrow.creator && !escrow.is_beneficiary(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    // Without an arbiter nobody could resolve the dispute
+    if escrow.arbiter.is_none() {
+        return Err(ContractError::NoArbiter {});
+    }
+
+    if !escrow.is_funded() {
+        return Err(ContractError::NotFunded {});
+    }
+
+    if escrow.disputed {
+        return Err(ContractError::Disputed {});
+    }
+
+    escrow.disputed = true;
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    // The arbiter settles the escrow even once it expired, so the sweep can pass over it.
+    // Resolving the dispute completes the escrow, which then stays out of the index.
+    remove_expiry(deps.storage, &escrow);
+    record_history(deps.storage, escrow_id, EscrowEventKind::Disputed, &info.sender, env.block.time.seconds())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "raise_dispute")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("raised_by", info.sender))
+}
+
+pub fn execute_resolve_dispute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    award_to_beneficiary: bool,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Only the arbiter can resolve a dispute
+    if escrow.arbiter.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    if !escrow.disputed {
+        return Err(ContractError::NotDisputed {});
+    }
+
+    // The arbiter's decision settles the escrow, a beneficiary change cannot hold it back
+    escrow.disputed = false;
+    escrow.beneficiary_change = None;
+
+    let response = Response::new()
+        .add_attribute("method", "resolve_dispute")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("award_to_beneficiary", award_to_beneficiary.to_string());
+
+    // Either pay out the beneficiary or refund the creator
+    let now = env.block.time.seconds();
+    if award_to_beneficiary {
+        release_escrow(deps, &env, &mut escrow, &info.sender, response)
+    } else {
+        let (bank_msgs, event) = refund_escrow(
+            deps.storage,
+            &mut escrow,
+            EscrowStatus::Cancelled,
+            &info.sender,
+            now,
+        )?;
+        Ok(response
+            .add_messages(bank_msgs)
+            .add_event(event)
+            .add_attribute("refunded_to", escrow.creator.to_string()))
+    }
+}
+
+pub fn execute_settle_cancellation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    to_beneficiary: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    // The arbiter settles on its own, the parties have to agree with each other
+    let is_arbiter = escrow.arbiter.as_ref() == Some(&info.sender);
+    if !is_arbiter && info.sender != escrow.creator && !escrow.is_beneficiary(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.disputed && !is_arbiter {
+        return Err(ContractError::Disputed {});
+    }
+
+    // The beneficiary part has to come out of the escrowed funds, the rest goes back
+    let to_beneficiary: Vec<Coin> = to_beneficiary.into_iter().filter(|coin| !coin.amount.is_zero()).collect();
+    let mut to_creator = escrow.amount.clone();
+    for coin in to_beneficiary.iter() {
+        match to_creator.iter_mut().find(|held| held.denom == coin.denom) {
+            Some(held) if held.amount >= coin.amount => held.amount -= coin.amount,
+            _ => return Err(ContractError::InvalidSettlement {}),
+        }
+    }
+    to_creator.retain(|coin| !coin.amount.is_zero());
+
+    let mut response = Response::new()
+        .add_attribute("method", "settle_cancellation")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("sender", info.sender.to_string());
+
+    // Agreeing to a different split replaces the previous proposal
+    if !is_arbiter {
+        let mut settlement = match escrow.settlement.take() {
+            Some(settlement) if settlement.to_beneficiary == to_beneficiary => settlement,
+            _ => Settlement {
+                to_beneficiary: to_beneficiary.clone(),
+                agreed_by: vec![],
+            },
+        };
+        if !settlement.agreed_by.contains(&info.sender) {
+            settlement.agreed_by.push(info.sender.clone());
+        }
+        let agreed = settlement.agreed_by.contains(&escrow.creator)
+            && escrow
+                .beneficiaries
+                .iter()
+                .all(|(beneficiary, _)| settlement.agreed_by.contains(beneficiary));
+        escrow.settlement = Some(settlement);
+
+        if !agreed {
+            ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+            let now = env.block.time.seconds();
+            record_history(deps.storage, escrow_id, EscrowEventKind::SettlementProposed, &info.sender, now)?;
+            return Ok(response.add_attribute("settled", "false"));
+        }
+    }
+
+    close_escrow(
+        deps.storage,
+        &mut escrow,
+        EscrowStatus::Cancelled,
+        &info.sender,
+        env.block.time.seconds(),
+    )?;
+
+    for (beneficiary, share) in split_shares(&to_beneficiary, &escrow.beneficiaries, &escrow.remainder_policy) {
+        response = response.add_message(BankMsg::Send {
+            to_address: escrow.payout_address(&beneficiary).to_string(),
+            amount: share,
+        });
+    }
+    if !to_creator.is_empty() {
+        response = response.add_message(BankMsg::Send {
+            to_address: escrow.creator.to_string(),
+            amount: to_creator.clone(),
+        });
+    }
+
+    let event = escrow_event(EVENT_ESCROW_CANCELLED, &escrow)
+        .add_attribute("creator", escrow.creator.to_string())
+        .add_attribute("amount", coins_to_string(&to_creator))
+        .add_attribute("denom", denoms_to_string(&to_creator))
+        .add_attribute("reason", "settled")
+        .add_attribute("to_beneficiary", coins_to_string(&to_beneficiary));
+
+    Ok(response
+        .add_attribute("settled", "true")
+        .add_attribute("to_beneficiary", coins_to_string(&to_beneficiary))
+        .add_attribute("to_creator", coins_to_string(&to_creator))
+        .add_event(event))
+}
+
+pub fn execute_transfer_creator(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    new_creator: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Only the current creator can hand off the escrow
+    if escrow.creator != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    let new_creator = deps.api.addr_validate(&new_creator)?;
+    if new_creator == env.contract.address {
+        return Err(ContractError::InvalidCreator {});
+    }
+    let old_creator = std::mem::replace(&mut escrow.creator, new_creator.clone());
+
+    // The new creator cannot approve, so drop any votes they cast as an approver and
+    // make sure the remaining approvers can still reach the threshold. The previous
+    // creator's auto-approval stays as an ordinary approval.
+    escrow.auto_approve_creator = false;
+    escrow.approvals.retain(|approval| approval.approver != new_creator);
+    escrow.cancel_votes.retain(|addr| *addr != new_creator);
+    let eligible_approvers = escrow.eligible_approvers();
+    if eligible_approvers == 0 {
+        return Err(ContractError::InvalidApprover {});
+    }
+    if escrow.required_approvals() > eligible_approvers || escrow.release_weight() > escrow.eligible_weight() {
+        return Err(ContractError::InvalidThreshold {});
+    }
+
+    // Move the escrow to the new creator's index
+    ESCROWS_BY_CREATOR.remove(deps.storage, (&old_creator, escrow_id));
+    ESCROWS_BY_CREATOR.save(deps.storage, (&new_creator, escrow_id), &())?;
+
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    let now = env.block.time.seconds();
+    record_history(deps.storage, escrow_id, EscrowEventKind::CreatorTransferred, &info.sender, now)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "transfer_creator")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("old_creator", old_creator)
+        .add_attribute("new_creator", new_creator))
+}
+
+pub fn execute_replace_approver(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    old: String,
+    new: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    if escrow.creator != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    // Once someone approved, the approver set is fixed
+    if escrow.has_outside_approvals() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let old = deps.api.addr_validate(&old)?;
+    let new = deps.api.addr_validate(&new)?;
+    let position = escrow
+        .approvers
+        .iter()
+        .position(|approver| *approver == old)
+        .ok_or(ContractError::InvalidApprover {})?;
+    if escrow.is_approver(&new) {
+        return Err(ContractError::DuplicateApprover {});
+    }
+    if new == env.contract.address {
+        return Err(ContractError::InvalidApprover {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if config.block_beneficiary_approver && escrow.is_beneficiary(&new) {
+        return Err(ContractError::BeneficiaryCannotApprove {});
+    }
+
+    // The old approver's votes go with them, including an auto-approved creator's approval.
+    // The creator cannot approve, so the remaining approvers must still reach the threshold.
+    escrow.approvers[position] = new.clone();
+    escrow.approvals.retain(|approval| approval.approver != old);
+    escrow.cancel_votes.retain(|addr| *addr != old);
+    if old == escrow.creator {
+        escrow.auto_approve_creator = false;
+    }
+    let eligible_approvers = escrow.eligible_approvers();
+    if eligible_approvers == 0 {
+        return Err(ContractError::InvalidApprover {});
+    }
+    if escrow.required_approvals() > eligible_approvers || escrow.release_weight() > escrow.eligible_weight() {
+        return Err(ContractError::InvalidThreshold {});
+    }
+
+    // A delegate acted for the old approver only
+    APPROVAL_DELEGATES.remove(deps.storage, (escrow_id, &old));
+    ESCROWS_BY_APPROVER.remove(deps.storage, (&old, escrow_id));
+    ESCROWS_BY_APPROVER.save(deps.storage, (&new, escrow_id), &())?;
+
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    let now = env.block.time.seconds();
+    record_history(deps.storage, escrow_id, EscrowEventKind::ApproverReplaced, &info.sender, now)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "replace_approver")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("old_approver", old)
+        .add_attribute("new_approver", new))
+}
+
+pub fn execute_propose_beneficiary_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    old_beneficiary: Option<String>,
+    new_beneficiary: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    if escrow.creator != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    // A disputed escrow is up to the arbiter
+    if escrow.disputed {
+        return Err(ContractError::Disputed {});
+    }
+
+    // One change at a time; the creator can withdraw the pending one first
+    if escrow.beneficiary_change.is_some() {
+        return Err(ContractError::BeneficiaryChangePending {});
+    }
+
+    let old = match old_beneficiary {
+        Some(old) => deps.api.addr_validate(&old)?,
+        None => match escrow.beneficiaries.as_slice() {
+            [(only, _)] => only.clone(),
+            _ => return Err(ContractError::InvalidBeneficiary {}),
+        },
+    };
+    let new = deps.api.addr_validate(&new_beneficiary)?;
+    if !escrow.is_beneficiary(&old) || escrow.is_beneficiary(&new) || new == env.contract.address {
+        return Err(ContractError::InvalidBeneficiary {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if config.block_beneficiary_approver && escrow.is_approver(&new) {
+        return Err(ContractError::BeneficiaryCannotApprove {});
+    }
+
+    // An auto-approved creator backs their own proposal, as they back the release
+    let approved_by = if escrow.auto_approve_creator { vec![escrow.creator.clone()] } else { vec![] };
+    escrow.beneficiary_change = Some(BeneficiaryChange {
+        old: old.clone(),
+        new: new.clone(),
+        approved_by,
+    });
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    let now = env.block.time.seconds();
+    record_history(deps.storage, escrow_id, EscrowEventKind::BeneficiaryChangeProposed, &info.sender, now)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "propose_beneficiary_change")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("old_beneficiary", old)
+        .add_attribute("new_beneficiary", new))
+}
+
+pub fn execute_approve_beneficiary_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    if !escrow.is_approver(&info.sender) {
+        return Err(ContractError::NotAnApprover {});
+    }
+    if !escrow.is_eligible(&info.sender) {
+        return Err(ContractError::CannotSelfApprove {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    // The disputed payout must not be redirected while the arbiter decides
+    if escrow.disputed {
+        return Err(ContractError::Disputed {});
+    }
+
+    let mut change = escrow.beneficiary_change.take().ok_or(ContractError::NoBeneficiaryChange {})?;
+    if change.approved_by.contains(&info.sender) {
+        return Err(ContractError::AlreadyApproved {});
+    }
+    change.approved_by.push(info.sender.clone());
+
+    let response = Response::new()
+        .add_attribute("method", "approve_beneficiary_change")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("approver", info.sender.to_string())
+        .add_attribute("approvals", change.approved_by.len().to_string());
+
+    // The change needs the same approver weight as the release
+    let approved_weight: u64 = change.approved_by.iter().map(|addr| escrow.approver_weight(addr)).sum();
+    if approved_weight < escrow.release_weight() {
+        escrow.beneficiary_change = Some(change);
+        ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+        return Ok(response.add_attribute("applied", "false"));
+    }
+
+    // The new beneficiary takes over the old one's share, but not its acceptance or
+    // payout address
+    let BeneficiaryChange { old, new, .. } = change;
+    for (beneficiary, _) in escrow.beneficiaries.iter_mut() {
+        if *beneficiary == old {
+            *beneficiary = new.clone();
+        }
+    }
+    escrow.accepted_by.retain(|addr| *addr != old);
+    escrow.payout_overrides.retain(|(beneficiary, _)| *beneficiary != old);
+    ESCROWS_BY_BENEFICIARY.remove(deps.storage, (&old, escrow_id));
+    ESCROWS_BY_BENEFICIARY.save(deps.storage, (&new, escrow_id), &())?;
+
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    let now = env.block.time.seconds();
+    record_history(deps.storage, escrow_id, EscrowEventKind::BeneficiaryChanged, &info.sender, now)?;
+
+    Ok(response
+        .add_attribute("applied", "true")
+        .add_attribute("old_beneficiary", old)
+        .add_attribute("new_beneficiary", new))
+}
+
+pub fn execute_cancel_beneficiary_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    if escrow.creator != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    if escrow.beneficiary_change.take().is_none() {
+        return Err(ContractError::NoBeneficiaryChange {});
+    }
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    let now = env.block.time.seconds();
+    record_history(deps.storage, escrow_id, EscrowEventKind::BeneficiaryChangeCancelled, &info.sender, now)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "cancel_beneficiary_change")
+        .add_attribute("escrow_id", escrow_id.to_string()))
+}
+
+pub fn execute_top_up(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Only the creator can add funds
+    if info.sender != escrow.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    // The amount is fixed once anyone signed off on it
+    if escrow.has_outside_approvals() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !escrow.is_funded() {
+        return Err(ContractError::NotFunded {});
+    }
+
+    let added: Vec<Coin> = info
+        .funds
+        .into_iter()
+        .filter(|coin| !coin.amount.is_zero())
+        .collect();
+    if added.is_empty() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    for coin in added.iter() {
+        let held = escrow
+            .amount
+            .iter_mut()
+            .find(|held| held.denom == coin.denom)
+            .ok_or(ContractError::DenomMismatch {})?;
+        held.amount += coin.amount;
+    }
+
+    // A larger amount may reach a stricter approval tier
+    let config = CONFIG.load(deps.storage)?;
+    apply_approval_tiers(&config, &mut escrow);
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    lock_funds(deps.storage, &added)?;
+    record_history(deps.storage, escrow_id, EscrowEventKind::ToppedUp, &info.sender, env.block.time.seconds())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "top_up")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("added", coins_to_string(&added))
+        .add_attribute("amount", coins_to_string(&escrow.amount)))
+}
+
+pub fn execute_update_description(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    description: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+
+    // Only the creator can change the terms
+    if info.sender != escrow.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    // Terms are fixed once anyone signed off on them
+    if escrow.has_outside_approvals() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    if description.chars().count() > config.max_description_length as usize {
+        return Err(ContractError::DescriptionTooLong {});
+    }
+
+    let old_description = std::mem::replace(&mut escrow.description, description);
+    ESCROWS.save(deps.storage, escrow_id, &escrow)?;
+    let now = env.block.time.seconds();
+    record_history(deps.storage, escrow_id, EscrowEventKind::DescriptionUpdated, &info.sender, now)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_description")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("old_description", old_description)
+        .add_attribute("new_description", escrow.description))
+}
+
+pub fn execute_save_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    approvers: Vec<String>,
+    threshold: Option<u32>,
+    description: String,
+) -> Result<Response, ContractError> {
+    // The rest of the settings is validated when an escrow is created from the template
+    if approvers.is_empty() {
+        return Err(ContractError::NoApprovers {});
+    }
+    if approvers.len() > MAX_APPROVERS {
+        return Err(ContractError::TooManyApprovers {});
+    }
+    let approvers = approvers
+        .iter()
+        .map(|approver| deps.api.addr_validate(approver))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    TEMPLATES.save(
+        deps.storage,
+        (&info.sender, &name),
+        &Template {
+            approvers,
+            threshold,
+            description,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "save_template")
+        .add_attribute("owner", info.sender)
+        .add_attribute("name", name))
+}
+
+pub fn execute_create_from_template(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    beneficiary: String,
+) -> Result<Response, ContractError> {
+    let template = TEMPLATES
+        .may_load(deps.storage, (&info.sender, &name))?
+        .ok_or(ContractError::TemplateNotFound {})?;
+
+    let msg = CreateEscrowMsg {
+        beneficiaries: vec![(beneficiary, 1)],
+        approvers: template.approvers.into_iter().map(String::from).collect(),
+        description: template.description,
+        threshold: template.threshold,
+        ..Default::default()
+    };
+    let response = execute_create_escrow(deps, env, info, msg)?;
+    Ok(response.add_attribute("template", name))
+}
+
+pub fn execute_set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let mut config = assert_owner(deps.storage, &info.sender)?;
+
+    config.paused = paused;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_paused")
+        .add_attribute("paused", paused.to_string()))
+}
+
+pub fn execute_update_allowed_denoms(
+    deps: DepsMut,
+    info: MessageInfo,
+    allowed_denoms: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let mut config = assert_owner(deps.storage, &info.sender)?;
+
+    let allowed = match allowed_denoms.as_ref() {
+        Some(denoms) => denoms.join(","),
+        None => "*".to_string(),
+    };
+    config.allowed_denoms = allowed_denoms;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_allowed_denoms")
+        .add_attribute("allowed_denoms", allowed))
+}
+
+pub fn execute_update_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    rate_limit: Option<RateLimit>,
+) -> Result<Response, ContractError> {
+    let mut config = assert_owner(deps.storage, &info.sender)?;
+    validate_rate_limit(rate_limit.as_ref())?;
+
+    let limit = match rate_limit.as_ref() {
+        Some(rate_limit) => format!("{}/{}s", rate_limit.max_creations, rate_limit.window),
+        None => "none".to_string(),
+    };
+    config.rate_limit = rate_limit;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_rate_limit")
+        .add_attribute("rate_limit", limit))
+}
+
+pub fn execute_update_default_threshold_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    policy: Option<ApprovalMode>,
+) -> Result<Response, ContractError> {
+    let mut config = assert_owner(deps.storage, &info.sender)?;
+
+    let attribute = match policy {
+        Some(ApprovalMode::Unanimous) => "unanimous",
+        Some(ApprovalMode::Majority) => "majority",
+        Some(ApprovalMode::Single) => "single",
+        None => "none",
+    };
+    config.default_threshold_policy = policy;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_default_threshold_policy")
+        .add_attribute("policy", attribute))
+}
+
+pub fn execute_update_fee_rounding(
+    deps: DepsMut,
+    info: MessageInfo,
+    rounding: FeeRounding,
+) -> Result<Response, ContractError> {
+    let mut config = assert_owner(deps.storage, &info.sender)?;
+
+    let attribute = match rounding {
+        FeeRounding::Floor => "floor",
+        FeeRounding::Ceil => "ceil",
+        FeeRounding::HalfEven => "half_even",
+    };
+    config.fee_rounding = rounding;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_fee_rounding")
+        .add_attribute("rounding", attribute))
+}
+
+pub fn execute_update_approval_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    tiers: Vec<ApprovalTier>,
+) -> Result<Response, ContractError> {
+    let mut config = assert_owner(deps.storage, &info.sender)?;
+
+    validate_approval_tiers(&tiers)?;
+    let count = tiers.len();
+    config.approval_tiers = tiers;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_approval_tiers")
+        .add_attribute("tiers", count.to_string()))
+}
+
+pub fn execute_update_creator_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: Option<bool>,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut config = assert_owner(deps.storage, &info.sender)?;
+
+    if let Some(enabled) = enabled {
+        config.creator_allowlist_enabled = enabled;
+        CONFIG.save(deps.storage, &config)?;
+    }
+    for creator in add.iter() {
+        CREATOR_ALLOWLIST.save(deps.storage, &deps.api.addr_validate(creator)?, &())?;
+    }
+    for creator in remove.iter() {
+        CREATOR_ALLOWLIST.remove(deps.storage, &deps.api.addr_validate(creator)?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "update_creator_allowlist")
+        .add_attribute("enabled", config.creator_allowlist_enabled.to_string())
+        .add_attribute("added", add.join(","))
+        .add_attribute("removed", remove.join(",")))
+}
+
+pub fn execute_set_denom_alias(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    alias: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+
+    match alias.as_ref() {
+        Some(alias) => DENOM_ALIASES.save(deps.storage, &denom, alias)?,
+        None => DENOM_ALIASES.remove(deps.storage, &denom),
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "set_denom_alias")
+        .add_attribute("denom", denom)
+        .add_attribute("alias", alias.unwrap_or_default()))
+}
+
+pub fn execute_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: OwnershipAction,
+) -> Result<Response, ContractError> {
+    let response = Response::new().add_attribute("method", "update_ownership");
+
+    match action {
+        OwnershipAction::TransferOwnership { new_owner, expiry } => {
+            assert_owner(deps.storage, &info.sender)?;
+            let new_owner = deps.api.addr_validate(&new_owner)?;
+            if matches!(expiry, Some(expiry) if expiry <= env.block.time.seconds()) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+            PENDING_OWNER.save(
+                deps.storage,
+                &PendingOwner {
+                    new_owner: new_owner.clone(),
+                    expiry,
+                },
+            )?;
+            Ok(response
+                .add_attribute("action", "transfer_ownership")
+                .add_attribute("pending_owner", new_owner))
+        }
+        OwnershipAction::AcceptOwnership {} => {
+            let pending = PENDING_OWNER
+                .may_load(deps.storage)?
+                .ok_or(ContractError::NoPendingOwner {})?;
+            if pending.new_owner != info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            if matches!(pending.expiry, Some(expiry) if env.block.time.seconds() > expiry) {
+                return Err(ContractError::OwnershipTransferExpired {});
+            }
+            PENDING_OWNER.remove(deps.storage);
+            CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+                config.admin = pending.new_owner.clone();
+                Ok(config)
+            })?;
+            Ok(response
+                .add_attribute("action", "accept_ownership")
+                .add_attribute("owner", pending.new_owner))
+        }
+        OwnershipAction::CancelTransfer {} => {
+            assert_owner(deps.storage, &info.sender)?;
+            if !PENDING_OWNER.exists(deps.storage) {
+                return Err(ContractError::NoPendingOwner {});
+            }
+            PENDING_OWNER.remove(deps.storage);
+            Ok(response.add_attribute("action", "cancel_transfer"))
+        }
+    }
+}
+
+pub fn execute_propose_emergency_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    to: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    let to = deps.api.addr_validate(&to)?;
+
+    let escrow = load_escrow(deps.storage, escrow_id)?;
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    // Proposing again restarts the timelock
+    let now = env.block.time.seconds();
+    EMERGENCY_WITHDRAWALS.save(deps.storage, escrow_id, &EmergencyWithdraw { to: to.clone(), proposed_at: now })?;
+    record_history(deps.storage, escrow_id, EscrowEventKind::EmergencyWithdrawProposed, &info.sender, now)?;
+
+    let executable_at = (now + EMERGENCY_WITHDRAW_DELAY).to_string();
+    Ok(Response::new()
+        .add_attribute("method", "propose_emergency_withdraw")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_event(escrow_event(EVENT_EMERGENCY_WITHDRAW_PROPOSED, &escrow)
+            .add_attribute("to", to)
+            .add_attribute("executable_at", executable_at)))
+}
+
+pub fn execute_emergency_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+
+    let proposal = EMERGENCY_WITHDRAWALS
+        .may_load(deps.storage, escrow_id)?
+        .ok_or(ContractError::NoEmergencyWithdraw {})?;
+    let executable_at = proposal.proposed_at + EMERGENCY_WITHDRAW_DELAY;
+    let now = env.block.time.seconds();
+    if now < executable_at {
+        return Err(ContractError::EmergencyWithdrawLocked { executable_at });
+    }
+
+    let mut escrow = load_escrow(deps.storage, escrow_id)?;
+    if escrow.is_completed() {
+        return Err(ContractError::EscrowCompleted {});
+    }
+
+    EMERGENCY_WITHDRAWALS.remove(deps.storage, escrow_id);
+    close_escrow(deps.storage, &mut escrow, EscrowStatus::Cancelled, &info.sender, now)?;
+
+    let event = escrow_event(EVENT_EMERGENCY_WITHDRAW_EXECUTED, &escrow)
+        .add_attribute("to", proposal.to.to_string())
+        .add_attribute("amount", coins_to_string(&escrow.amount))
+        .add_attribute("denom", denoms_to_string(&escrow.amount));
+    let mut response = Response::new()
+        .add_attribute("method", "execute_emergency_withdraw")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_event(event);
+    if escrow.is_funded() {
+        response = response.add_message(BankMsg::Send {
+            to_address: proposal.to.to_string(),
+            amount: escrow.amount,
+        });
+    }
+    Ok(response)
+}
+
+pub fn execute_withdraw_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin && Some(&info.sender) != config.fee_recipient.as_ref() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Fees only accrue while there is a fee recipient to withdraw them to
+    let accrued = FEE_ACCRUED.may_load(deps.storage, &denom)?.unwrap_or_default();
+    let fee_recipient = match config.fee_recipient {
+        Some(fee_recipient) if !accrued.is_zero() => fee_recipient,
+        _ => return Err(ContractError::NoFeesAccrued {}),
+    };
+
+    FEE_ACCRUED.remove(deps.storage, &denom);
+    let amount = Coin { denom, amount: accrued };
+
+    Ok(Response::new()
+        .add_attribute("method", "withdraw_fees")
+        .add_attribute("fee_recipient", fee_recipient.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_message(BankMsg::Send {
+            to_address: fee_recipient.to_string(),
+            amount: vec![amount],
+        }))
+}
+
+fn validate_approval_tiers(tiers: &[ApprovalTier]) -> Result<(), ContractError> {
+    if tiers.iter().any(|tier| tier.denom.is_empty() || tier.min_amount.is_zero()) {
+        return Err(ContractError::InvalidApprovalTier {});
+    }
+    Ok(())
+}
+
+fn validate_rate_limit(rate_limit: Option<&RateLimit>) -> Result<(), ContractError> {
+    if matches!(rate_limit, Some(rate_limit) if rate_limit.window == 0 || rate_limit.max_creations == 0) {
+        return Err(ContractError::InvalidRateLimit {});
+    }
+    Ok(())
+}
+
+/// Raises the escrow's required approvals to the strictest approval tier its amount reaches.
+/// Escrows released by weight are left alone.
+fn apply_approval_tiers(config: &Config, escrow: &mut Escrow) {
+    if escrow.required_weight.is_some() {
+        return;
+    }
+    let tiered = config
+        .approval_tiers
+        .iter()
+        .filter(|tier| {
+            escrow
+                .amount
+                .iter()
+                .any(|coin| coin.denom == tier.denom && coin.amount >= tier.min_amount)
+        })
+        .map(|tier| escrow.mode_required_approvals(&tier.mode) as u32)
+        .max();
+    if let Some(required) = tiered {
+        escrow.required_approvals = escrow.required_approvals.max(required);
+    }
+}
+
+/// Loads an escrow, failing with `EscrowNotFound` for an unknown ID
+fn load_escrow(storage: &dyn Storage, escrow_id: u64) -> Result<Escrow, ContractError> {
+    ESCROWS.may_load(storage, escrow_id)?.ok_or(ContractError::EscrowNotFound {})
+}
+
+/// Loads an escrow for a query, naming the unknown ID in the error
+fn query_load_escrow(storage: &dyn Storage, escrow_id: u64) -> StdResult<Escrow> {
+    ESCROWS
+        .may_load(storage, escrow_id)?
+        .ok_or_else(|| StdError::not_found(format!("Escrow {}", escrow_id)))
+}
+
+/// Loads the config, failing unless the sender is the contract owner (the config admin).
+/// Every admin action goes through this check.
+fn assert_owner(storage: &dyn Storage, sender: &Addr) -> Result<Config, ContractError> {
+    let config = CONFIG.load(storage)?;
+    if *sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(config)
+}
+
+/// Fails if `release_escrow` could not release the escrow right now
+fn check_releasable(deps: Deps, env: &Env, escrow: &Escrow) -> Result<(), ContractError> {
+    // The payout must wait until the beneficiaries are settled
+    if escrow.beneficiary_change.is_some() {
+        return Err(ContractError::BeneficiaryChangePending {});
+    }
+
+    if !dependency_released(deps.storage, escrow)? {
+        return Err(ContractError::DependencyNotReleased {});
+    }
+
+    // Never emit an empty send for an escrow whose stored amount got out of shape
+    if escrow.amount.is_empty() || escrow.amount.iter().any(|coin| coin.denom.is_empty() || coin.amount.is_zero()) {
+        return Err(ContractError::ConditionsNotMet {});
+    }
+
+    // Make sure the contract can actually cover the sends before completing the escrow
+    for coin in escrow.amount.iter() {
+        let balance = deps.querier.query_balance(&env.contract.address, &coin.denom)?;
+        if balance.amount < coin.amount {
+            return Err(ContractError::InsufficientContractBalance {});
+        }
+    }
+
+    Ok(())
+}
+
+/// Marks the escrow as released, saves it and adds the bank messages paying out the
+/// beneficiaries (minus the platform fee) to the response. Fails without touching the
+/// escrow if its stored amount is malformed or the contract does not hold the escrowed funds.
+fn release_escrow(
+    deps: DepsMut,
+    env: &Env,
+    escrow: &mut Escrow,
+    actor: &Addr,
+    mut response: Response,
+) -> Result<Response, ContractError> {
+    check_releasable(deps.as_ref(), env, escrow)?;
+
+    // Take the platform fee out of the released amount
+    let config = CONFIG.load(deps.storage)?;
+    let (payout, fee) = split_fee(&escrow.amount, config.fee_bps, &config.fee_rounding);
+
+    // Mark as released
+    escrow.status = EscrowStatus::Released;
+    escrow.completed_at = Some(env.block.time.seconds());
+    ESCROWS.save(deps.storage, escrow.id, escrow)?;
+    remove_expiry(deps.storage, escrow);
+    record_escrow_completed(deps.storage, &escrow.status, &escrow.amount)?;
+    record_history(deps.storage, escrow.id, EscrowEventKind::Released, actor, env.block.time.seconds())?;
+
+    // Add a bank message sending each beneficiary their share, to their payout address if
+    // they set one. A release paid out in a single send reopens the escrow if that send
+    // fails (see `reply`). When the payout is split over several sends a failure aborts the
+    // whole transaction instead, as the other sends may already have gone through. The same
+    // goes for escrows with a release hook, which must not hear of a release that failed, and
+    // for releases accruing a fee, which the reopened escrow would otherwise pay twice.
+    let sends: Vec<BankMsg> = split_shares(&payout, &escrow.beneficiaries, &escrow.remainder_policy)
+        .into_iter()
+        .map(|(beneficiary, share)| BankMsg::Send {
+            to_address: escrow.payout_address(&beneficiary).to_string(),
+            amount: share,
+        })
+        .collect();
+    let has_fee = config.fee_recipient.is_some() && !fee.is_empty();
+    if let ([send], false) = (sends.as_slice(), has_fee || escrow.release_hook.is_some()) {
+        response = response.add_submessage(SubMsg::reply_on_error(send.clone(), escrow.id));
+    } else {
+        response = response.add_messages(sends);
+    }
+
+    response = response
+        .add_attribute("released", "true")
+        .add_attribute("released_to", beneficiaries_to_string(escrow))
+        .add_attribute("amount_released", coins_to_string(&payout));
+
+    let mut event = escrow_event(EVENT_ESCROW_RELEASED, escrow)
+        .add_attribute("beneficiary", beneficiaries_to_string(escrow))
+        .add_attribute("amount", coins_to_string(&escrow.amount))
+        .add_attribute("denom", denoms_to_string(&escrow.amount));
+
+    // The fee stays in the contract until the fee recipient withdraws it
+    if let (Some(fee_recipient), false) = (config.fee_recipient, fee.is_empty()) {
+        accrue_fees(deps.storage, &fee)?;
+        response = response
+            .add_attribute("fee_recipient", fee_recipient.to_string())
+            .add_attribute("fee", coins_to_string(&fee));
+        event = event.add_attribute("fee", coins_to_string(&fee));
+    }
+
+    // Notify the hook contract last, as a plain message so its failure reverts the release
+    if let (Some(hook), Some(hook_msg)) = (escrow.release_hook.as_ref(), escrow.release_hook_msg.as_ref()) {
+        response = response.add_message(WasmMsg::Execute {
+            contract_addr: hook.to_string(),
+            msg: hook_msg.clone(),
+            funds: vec![],
+        });
+    }
+
+    Ok(response.add_event(event))
+}
+
+/// Handles a failed release send. The reply ID is the ID of the escrow being released,
+/// which is moved back to `Open` so the release can be retried.
+#[entry_point]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let error = match msg.result {
+        SubMsgResult::Err(error) => error,
+        SubMsgResult::Ok(_) => return Ok(Response::new()),
+    };
+
+    let mut escrow = ESCROWS.load(deps.storage, msg.id)?;
+    escrow.status = EscrowStatus::Open;
+    escrow.completed_at = None;
+    ESCROWS.save(deps.storage, escrow.id, &escrow)?;
+    if let Some(expires_at) = escrow.expires_at {
+        ESCROWS_BY_EXPIRES_AT.save(deps.storage, (expires_at, escrow.id), &())?;
+    }
+    record_escrow_reopened(deps.storage, &escrow.amount)?;
+    let now = env.block.time.seconds();
+    record_history(deps.storage, escrow.id, EscrowEventKind::ReleaseFailed, &env.contract.address, now)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "release_failed")
+        .add_attribute("escrow_id", escrow.id.to_string())
+        .add_attribute("error", error))
+}
+
+/// Moves the escrow into the given terminal status on behalf of `actor` and saves it. Like
+/// released escrows, it stays in the indexes.
+fn close_escrow(
+    storage: &mut dyn Storage,
+    escrow: &mut Escrow,
+    status: EscrowStatus,
+    actor: &Addr,
+    now: u64,
+) -> StdResult<()> {
+    let kind = match status {
+        EscrowStatus::Released => EscrowEventKind::Released,
+        EscrowStatus::Expired => EscrowEventKind::Expired,
+        _ => EscrowEventKind::Cancelled,
+    };
+
+    // Mark as completed
+    escrow.status = status;
+    escrow.completed_at = Some(now);
+
+    // Save updated escrow
+    ESCROWS.save(storage, escrow.id, escrow)?;
+    remove_expiry(storage, escrow);
+    record_escrow_completed(storage, &escrow.status, &escrow.amount)?;
+    record_history(storage, escrow.id, kind, actor, now)
+}
+
+/// Drops a completed or disputed escrow from the expiration index
+fn remove_expiry(storage: &mut dyn Storage, escrow: &Escrow) {
+    if let Some(expires_at) = escrow.expires_at {
+        ESCROWS_BY_EXPIRES_AT.remove(storage, (expires_at, escrow.id));
+    }
+}
+
+/// Cancels the escrow like `refund_escrow`, but takes the cancellation fee out of the refund
+/// and accrues it when there is a fee recipient. Also returns the fee, empty when none was
+/// charged.
+fn cancel_by_creator(
+    storage: &mut dyn Storage,
+    config: &Config,
+    escrow: &mut Escrow,
+    actor: &Addr,
+    now: u64,
+) -> StdResult<(Vec<BankMsg>, Event, Vec<Coin>)> {
+    let (refund, fee) = split_fee(&escrow.amount, config.cancel_fee_bps, &config.fee_rounding);
+    if config.fee_recipient.is_none() || fee.is_empty() {
+        let (bank_msgs, event) = refund_escrow(storage, escrow, EscrowStatus::Cancelled, actor, now)?;
+        return Ok((bank_msgs, event, vec![]));
+    }
+
+    close_escrow(storage, escrow, EscrowStatus::Cancelled, actor, now)?;
+    accrue_fees(storage, &fee)?;
+
+    let mut bank_msgs = vec![];
+    if !refund.is_empty() {
+        bank_msgs.push(BankMsg::Send {
+            to_address: escrow.creator.to_string(),
+            amount: refund.clone(),
+        });
+    }
+
+    let event = escrow_event(EVENT_ESCROW_CANCELLED, escrow)
+        .add_attribute("creator", escrow.creator.to_string())
+        .add_attribute("amount", coins_to_string(&refund))
+        .add_attribute("denom", denoms_to_string(&refund))
+        .add_attribute("reason", "cancelled")
+        .add_attribute("fee", coins_to_string(&fee));
+
+    Ok((bank_msgs, event, fee))
+}
+
+/// Closes the escrow like `close_escrow` and returns the bank message refunding the escrowed funds to the creator,
+/// if it holds any, along with the matching `escrow_cancelled` event.
+fn refund_escrow(
+    storage: &mut dyn Storage,
+    escrow: &mut Escrow,
+    status: EscrowStatus,
+    actor: &Addr,
+    now: u64,
+) -> StdResult<(Vec<BankMsg>, Event)> {
+    close_escrow(storage, escrow, status, actor, now)?;
+
+    let event = escrow_event(EVENT_ESCROW_CANCELLED, escrow)
+        .add_attribute("creator", escrow.creator.to_string())
+        .add_attribute("amount", coins_to_string(&escrow.amount))
+        .add_attribute("denom", denoms_to_string(&escrow.amount))
+        .add_attribute("reason", match escrow.status {
+            EscrowStatus::Expired => "expired",
+            _ => "cancelled",
+        });
+
+    // Return funds to creator
+    let mut bank_msgs = vec![];
+    if escrow.is_funded() {
+        bank_msgs.push(BankMsg::Send {
+            to_address: escrow.creator.to_string(),
+            amount: escrow.amount.clone(),
+        });
+    }
+
+    Ok((bank_msgs, event))
+}
+
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetEscrow { escrow_id } => to_json_binary(&query_escrow(deps, env, escrow_id)?),
+        QueryMsg::GetEscrows { ids } => to_json_binary(&query_escrows(deps, env, ids)?),
+        QueryMsg::GetRole { escrow_id, address } => {
+            to_json_binary(&query_role(deps, env, escrow_id, address)?)
+        }
+        QueryMsg::WouldRelease { escrow_id, approver } => {
+            to_json_binary(&query_would_release(deps, env, escrow_id, approver)?)
+        }
+        QueryMsg::GetApprovalNonce { escrow_id, approver } => {
+            to_json_binary(&query_approval_nonce(deps, escrow_id, approver)?)
+        }
+        QueryMsg::CanCancel { escrow_id, address } => to_json_binary(&query_can_cancel(deps, escrow_id, address)?),
+        QueryMsg::GetApprovalStatus { escrow_id } => {
+            to_json_binary(&query_approval_status(deps, env, escrow_id)?)
+        }
+        QueryMsg::GetApprovals { escrow_id, start_after, limit } => {
+            to_json_binary(&query_approvals(deps, escrow_id, start_after, limit)?)
+        }
+        QueryMsg::GetTemplates { owner, start_after, limit } => {
+            to_json_binary(&query_templates(deps, owner, start_after, limit)?)
+        }
+        QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::GetCapabilities {} => to_json_binary(&query_capabilities(deps)?),
+        QueryMsg::Ownership {} => to_json_binary(&query_ownership(deps)?),
+        QueryMsg::GetStats {} => to_json_binary(&query_stats(deps)?),
+        QueryMsg::GetAccruedFees {} => to_json_binary(&query_accrued_fees(deps)?),
+        QueryMsg::CountEscrows { status } => to_json_binary(&query_count_escrows(deps, status)?),
+        QueryMsg::GetReconciliation { denom } => to_json_binary(&query_reconciliation(deps, env, denom)?),
+        QueryMsg::CheckInvariants { escrow_id } => to_json_binary(&query_check_invariants(deps, escrow_id)?),
+        QueryMsg::GetEscrowsByAddress { address, status, role, start_after, limit } => {
+            to_json_binary(&query_escrows_by_address(deps, env, address, status, role, start_after, limit)?)
+        }
+        QueryMsg::GetPendingApprovals { approver, start_after, limit, sort } => {
+            to_json_binary(&query_pending_approvals(deps, env, approver, start_after, limit, sort)?)
+        }
+        QueryMsg::GetEscrowsByDenom { denom, start_after, limit } => {
+            to_json_binary(&query_escrows_by_denom(deps, env, denom, start_after, limit)?)
+        }
+        QueryMsg::GetStaleEscrows { older_than_secs, start_after, limit } => {
+            to_json_binary(&query_stale_escrows(deps, env, older_than_secs, start_after, limit)?)
+        }
+        QueryMsg::GetExpiringEscrows { within_secs, start_after, limit } => {
+            to_json_binary(&query_expiring_escrows(deps, env, within_secs, start_after, limit)?)
+        }
+        QueryMsg::GetActionable { address, start_after, limit } => {
+            to_json_binary(&query_actionable(deps, env, address, start_after, limit)?)
+        }
+        QueryMsg::GetAllEscrows { status, include_completed, start_after, limit, order } => {
+            let include_completed = include_completed.unwrap_or(true);
+            to_json_binary(&query_all_escrows(deps, env, status, include_completed, start_after, limit, order)?)
+        }
+        QueryMsg::GetEscrowsByTimeRange { start_time, end_time, start_after, limit } => {
+            to_json_binary(&query_escrows_by_time_range(deps, env, start_time, end_time, start_after, limit)?)
+        }
+        QueryMsg::GetEscrowHistory { escrow_id, start_after, limit } => {
+            to_json_binary(&query_escrow_history(deps, escrow_id, start_after, limit)?)
+        }
+    }
+}
+
+fn query_templates(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TemplateListResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(10) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let templates = TEMPLATES
+        .prefix(&owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (name, template) = item?;
+            Ok(TemplateResponse {
+                name,
+                approvers: template.approvers,
+                threshold: template.threshold,
+                description: template.description,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TemplateListResponse { templates })
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        admin: config.admin,
+        paused: config.paused,
+        fee_recipient: config.fee_recipient,
+        fee_bps: config.fee_bps,
+        cancel_fee_bps: config.cancel_fee_bps,
+        require_acceptance: config.require_acceptance,
+        max_description_length: config.max_description_length,
+        min_amounts: config.min_amounts,
+        allowed_denoms: config.allowed_denoms,
+        block_beneficiary_approver: config.block_beneficiary_approver,
+        two_phase_release: config.two_phase_release,
+        grace_period: config.grace_period,
+        creator_allowlist_enabled: config.creator_allowlist_enabled,
+        rate_limit: config.rate_limit,
+        default_threshold_policy: config.default_threshold_policy,
+        approval_tiers: config.approval_tiers,
+        fee_rounding: config.fee_rounding,
+    })
+}
+
+fn query_capabilities(deps: Deps) -> StdResult<CapabilitiesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let version = get_contract_version(deps.storage)?;
+
+    // Fees are only charged when there is a recipient for them
+    let has_fee_recipient = config.fee_recipient.is_some();
+    Ok(CapabilitiesResponse {
+        contract: version.contract,
+        version: version.version,
+        accepting_escrows: !config.paused,
+        release_fee: has_fee_recipient && config.fee_bps > 0,
+        fee_bps: config.fee_bps,
+        cancel_fee: has_fee_recipient && config.cancel_fee_bps > 0,
+        cancel_fee_bps: config.cancel_fee_bps,
+        denom_allowlist: config.allowed_denoms.is_some(),
+        min_amounts: !config.min_amounts.is_empty(),
+        require_acceptance: config.require_acceptance,
+        block_beneficiary_approver: config.block_beneficiary_approver,
+        two_phase_release: config.two_phase_release,
+        grace_period: config.grace_period,
+        creator_allowlist: config.creator_allowlist_enabled,
+        rate_limit: config.rate_limit.is_some(),
+        max_description_length: config.max_description_length,
+    })
+}
+
+fn query_ownership(deps: Deps) -> StdResult<OwnershipResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pending = PENDING_OWNER.may_load(deps.storage)?;
+    Ok(OwnershipResponse {
+        owner: config.admin,
+        pending_owner: pending.as_ref().map(|pending| pending.new_owner.clone()),
+        pending_expiry: pending.and_then(|pending| pending.expiry),
+    })
+}
+
+fn query_stats(deps: Deps) -> StdResult<StatsResponse> {
+    let stats = STATS.load(deps.storage)?;
+    let total_locked = TOTAL_LOCKED
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(StatsResponse {
+        total_escrows: stats.total_escrows,
+        open_count: stats.open_count,
+        completed_count: stats.completed_count,
+        total_locked,
+    })
+}
+
+fn query_accrued_fees(deps: Deps) -> StdResult<AccruedFeesResponse> {
+    let fees = FEE_ACCRUED
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, amount) = item?;
+            Ok(Coin { denom, amount })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AccruedFeesResponse { fees })
+}
+
+fn query_reconciliation(deps: Deps, env: Env, denom: String) -> StdResult<ReconciliationResponse> {
+    let locked_in_open_escrows = TOTAL_LOCKED.may_load(deps.storage, &denom)?.unwrap_or_default();
+    let accrued_fees = FEE_ACCRUED.may_load(deps.storage, &denom)?.unwrap_or_default();
+    let contract_balance = deps.querier.query_balance(&env.contract.address, &denom)?.amount;
+    let difference =
+        Int256::from(contract_balance) - Int256::from(locked_in_open_escrows) - Int256::from(accrued_fees);
+
+    Ok(ReconciliationResponse {
+        denom,
+        locked_in_open_escrows,
+        accrued_fees,
+        contract_balance,
+        difference,
+    })
+}
+
+fn query_check_invariants(deps: Deps, escrow_id: u64) -> StdResult<InvariantReport> {
+    let escrow = query_load_escrow(deps.storage, escrow_id)?;
+    let mut violations = vec![];
+
+    for (i, approval) in escrow.approvals.iter().enumerate() {
+        if !escrow.is_approver(&approval.approver) {
+            violations.push(format!("approval by {} who is not an approver", approval.approver));
+        }
+        if !escrow.is_eligible(&approval.approver) {
+            violations.push(format!("approval by the creator {}", approval.approver));
+        }
+        if escrow.approvals[..i].iter().any(|earlier| earlier.approver == approval.approver) {
+            violations.push(format!("duplicate approval by {}", approval.approver));
+        }
+    }
+    if escrow.approvals.len() > escrow.total_approvers() {
+        violations.push(format!(
+            "{} approvals for {} approvers",
+            escrow.approvals.len(),
+            escrow.total_approvers()
+        ));
+    }
+    if escrow.required_approvals == 0 {
+        violations.push("no approvals required".to_string());
+    }
+    if !escrow.approver_weights.is_empty() && escrow.approver_weights.len() != escrow.approvers.len() {
+        violations.push(format!(
+            "{} approver weights for {} approvers",
+            escrow.approver_weights.len(),
+            escrow.approvers.len()
+        ));
+    }
+
+    // Open escrows have no completion time, completed ones always have one
+    match (escrow.is_completed(), escrow.completed_at) {
+        (false, Some(_)) => violations.push("open escrow has a completion time".to_string()),
+        (true, None) => violations.push(format!("{:?} escrow has no completion time", escrow.status)),
+        _ => {}
+    }
+
+    Ok(InvariantReport { escrow_id, violations })
+}
+
+fn query_count_escrows(deps: Deps, status: Option<EscrowStatus>) -> StdResult<u64> {
+    let stats = STATS.load(deps.storage)?;
+    Ok(match status {
+        None => stats.total_escrows,
+        Some(EscrowStatus::Open) => stats.open_count,
+        Some(EscrowStatus::Released) => stats.released_count,
+        Some(EscrowStatus::Cancelled) => stats.cancelled_count,
+        Some(EscrowStatus::Expired) => stats.expired_count,
+    })
+}
+
+fn query_escrow(deps: Deps, env: Env, escrow_id: u64) -> StdResult<EscrowResponse> {
+    let escrow = query_load_escrow(deps.storage, escrow_id)?;
+    escrow_to_response(deps.storage, escrow, env.block.time.seconds())
+}
+
+fn query_escrows(deps: Deps, env: Env, ids: Vec<u64>) -> StdResult<EscrowListResponse> {
+    if ids.len() > MAX_BATCH_SIZE {
+        return Err(StdError::generic_err(format!("Cannot query more than {} escrows at once", MAX_BATCH_SIZE)));
+    }
+
+    let mut escrows = vec![];
+    for id in ids {
+        if let Some(escrow) = ESCROWS.may_load(deps.storage, id)? {
+            escrows.push(escrow_to_response(deps.storage, escrow, env.block.time.seconds())?);
+        }
+    }
+
+    Ok(EscrowListResponse { escrows })
+}
+
+fn query_role(deps: Deps, env: Env, escrow_id: u64, address: String) -> StdResult<RoleResponse> {
+    let escrow = query_load_escrow(deps.storage, escrow_id)?;
+    let addr = deps.api.addr_validate(&address)?;
+
+    let is_approver = escrow.is_approver(&addr);
+    let has_approved = escrow.has_current_approval(&addr, env.block.time.seconds());
+
+    // Runs the same checks as `execute_approve_release`
+    let can_approve = is_approver && check_can_approve(&escrow, &addr, env.block.time.seconds()).is_ok();
+
+    Ok(RoleResponse {
+        is_creator: addr == escrow.creator,
+        is_beneficiary: escrow.is_beneficiary(&addr),
+        is_approver,
+        has_approved,
+        can_approve,
+    })
+}
+
+fn query_would_release(deps: Deps, env: Env, escrow_id: u64, approver: String) -> StdResult<bool> {
+    let mut escrow = query_load_escrow(deps.storage, escrow_id)?;
+    let approver = deps.api.addr_validate(&approver)?;
+    let now = env.block.time.seconds();
+    if !escrow.is_approver(&approver) || check_can_approve(&escrow, &approver, now).is_err() {
+        return Ok(false);
+    }
+
+    // Applies the approval to a copy and runs the release decision of `execute_approve_release`
+    add_approval(&mut escrow, &approver, now, None);
+    let config = CONFIG.load(deps.storage)?;
+    Ok(matches!(releases_on_approval(deps, &env, &config, &escrow), Ok(true)))
+}
+
+fn query_approval_nonce(deps: Deps, escrow_id: u64, approver: String) -> StdResult<u64> {
+    let approver = deps.api.addr_validate(&approver)?;
+    Ok(APPROVAL_NONCES.may_load(deps.storage, (escrow_id, &approver))?.unwrap_or_default())
+}
+
+fn query_can_cancel(deps: Deps, escrow_id: u64, address: String) -> StdResult<CanCancelResponse> {
+    let escrow = query_load_escrow(deps.storage, escrow_id)?;
+    let address = deps.api.addr_validate(&address)?;
+
+    let reason = cancel_blocker(&escrow, &address).map(|(reason, _)| reason.to_string());
+    Ok(CanCancelResponse {
+        can_cancel: reason.is_none(),
+        reason,
+    })
+}
+
+fn query_approval_status(deps: Deps, env: Env, escrow_id: u64) -> StdResult<ApprovalStatusResponse> {
+    let escrow = query_load_escrow(deps.storage, escrow_id)?;
+
+    // Approvals older than the approval TTL no longer count
+    let now = env.block.time.seconds();
+    let required = escrow.required_approvals() as u32;
+    let current = escrow.current_approvals(now) as u32;
+    let current_approvals: Vec<&Approval> = escrow
+        .approvals
+        .iter()
+        .filter(|approval| escrow.is_approval_current(approval, now))
+        .collect();
+    let approved = current_approvals.iter().map(|approval| approval.approver.clone()).collect();
+    let comments = current_approvals
+        .iter()
+        .filter_map(|approval| Some((approval.approver.clone(), approval.comment.clone()?)))
+        .collect();
+    let pending = escrow
+        .approvers
+        .iter()
+        .filter(|approver| escrow.is_eligible(approver) && !escrow.has_current_approval(approver, now))
+        .cloned()
+        .collect();
+
+    Ok(ApprovalStatusResponse {
+        required,
+        current,
+        remaining: required.saturating_sub(current),
+        approved,
+        comments,
+        pending,
+        required_weight: escrow.release_weight(),
+        current_weight: escrow.current_weight(now),
+    })
+}
+
+fn query_approvals(
+    deps: Deps,
+    escrow_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ApprovalListResponse> {
+    let escrow = query_load_escrow(deps.storage, escrow_id)?;
+    let limit = limit.unwrap_or(10) as usize;
+
+    // Ordering by approver keeps the cursor valid when approvals are given or revoked
+    // between pages
+    let mut approvals: Vec<Approval> = escrow
+        .approvals
+        .into_iter()
+        .filter(|approval| match &start_after {
+            Some(start_after) => approval.approver.as_str() > start_after.as_str(),
+            None => true,
+        })
+        .collect();
+    approvals.sort_by(|a, b| a.approver.cmp(&b.approver));
+    approvals.truncate(limit);
+
+    Ok(ApprovalListResponse { approvals })
+}
+
+fn query_escrows_by_address(
+    deps: Deps,
+    env: Env,
+    address: String,
+    status: Option<EscrowStatus>,
+    role: Option<EscrowRole>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowListResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(10) as usize;
+
+    // Only scan the index of the requested role, or all of them
+    let indexes = match role {
+        Some(EscrowRole::Creator) => vec![ESCROWS_BY_CREATOR],
+        Some(EscrowRole::Beneficiary) => vec![ESCROWS_BY_BENEFICIARY],
+        Some(EscrowRole::Approver) => vec![ESCROWS_BY_APPROVER],
+        None => vec![ESCROWS_BY_CREATOR, ESCROWS_BY_BENEFICIARY, ESCROWS_BY_APPROVER],
+    };
+
+    // Apply pagination, counting only escrows matching the status filter
+    let mut escrows = vec![];
+    visit_role_escrows(deps.storage, &addr, &indexes, start_after, |id| {
+        if escrows.len() >= limit {
+            return Ok(false);
+        }
+        let escrow = ESCROWS.load(deps.storage, id)?;
+        if matches_status(&escrow, &status) {
+            escrows.push(escrow_to_response(deps.storage, escrow, env.block.time.seconds())?);
+        }
+        Ok(true)
+    })?;
+
+    Ok(EscrowListResponse { escrows })
+}
+
+/// Calls `visit` with the IDs of the address's escrows in the given role indexes, in
+/// ascending order and each ID once, until it returns false
+fn visit_role_escrows(
+    storage: &dyn Storage,
+    addr: &Addr,
+    indexes: &[Map<(&Addr, u64), ()>],
+    start_after: Option<u64>,
+    mut visit: impl FnMut(u64) -> StdResult<bool>,
+) -> StdResult<()> {
+    // Each role index yields the address's escrow IDs in ascending order, so merging
+    // the streams only reads as many index entries as the caller needs
+    let mut streams = indexes
+        .iter()
+        .map(|index| {
+            index
+                .prefix(addr)
+                .keys(storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        })
+        .collect::<Vec<_>>();
+    let mut heads = streams
+        .iter_mut()
+        .map(|stream| stream.next().transpose())
+        .collect::<StdResult<Vec<_>>>()?;
+
+    while let Some(id) = heads.iter().flatten().min().copied() {
+        // Advance every stream positioned on this ID so an address holding several
+        // roles in the same escrow only gets it once
+        for (head, stream) in heads.iter_mut().zip(streams.iter_mut()) {
+            if *head == Some(id) {
+                *head = stream.next().transpose()?;
+            }
+        }
+
+        if !visit(id)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn query_stale_escrows(
+    deps: Deps,
+    env: Env,
+    older_than_secs: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowListResponse> {
+    let limit = limit.unwrap_or(10) as usize;
+    let now = env.block.time.seconds();
+
+    // IDs follow creation order, so the scan can stop at the first escrow that is too young
+    let escrows = ESCROWS
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take_while(|item| match item {
+            Ok((_, escrow)) => now.saturating_sub(escrow.created_at) > older_than_secs,
+            Err(_) => true,
+        })
+        .filter(|item| match item {
+            Ok((_, escrow)) => !escrow.is_completed() && !escrow.can_be_released(now),
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| {
+            let (_, escrow) = item?;
+            escrow_to_response(deps.storage, escrow, now)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(EscrowListResponse { escrows })
+}
+
+fn query_expiring_escrows(
+    deps: Deps,
+    env: Env,
+    within_secs: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowListResponse> {
+    let limit = limit.unwrap_or(10) as usize;
+    let now = env.block.time.seconds();
+
+    // Expiration times don't follow creation order, so every remaining escrow is checked
+    let escrows = ESCROWS
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, escrow)) => {
+                !escrow.is_completed()
+                    && !escrow.is_expired(now)
+                    && matches!(escrow.expires_at, Some(expires_at) if expires_at - now <= within_secs)
+            }
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| {
+            let (_, escrow) = item?;
+            escrow_to_response(deps.storage, escrow, now)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(EscrowListResponse { escrows })
+}
+
+fn query_actionable(
+    deps: Deps,
+    env: Env,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ActionableResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(10) as usize;
+    let config = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+
+    // Completed escrows in the role indexes have no actions left and are skipped
+    let indexes = [ESCROWS_BY_CREATOR, ESCROWS_BY_BENEFICIARY, ESCROWS_BY_APPROVER];
+    let mut escrows = vec![];
+    visit_role_escrows(deps.storage, &addr, &indexes, start_after, |id| {
+        if escrows.len() >= limit {
+            return Ok(false);
+        }
+        let escrow = ESCROWS.load(deps.storage, id)?;
+        let actions = available_actions(&config, &escrow, &addr, now);
+        if !actions.is_empty() {
+            escrows.push(ActionableEscrow {
+                escrow: escrow_to_response(deps.storage, escrow, now)?,
+                actions,
+            });
         }
+        Ok(true)
+    })?;
+
+    Ok(ActionableResponse { escrows })
+}
+
+/// Actions the address can take on the escrow, mirroring the checks of their handlers
+fn available_actions(config: &Config, escrow: &Escrow, addr: &Addr, now: u64) -> Vec<EscrowAction> {
+    let mut actions = vec![];
+    if escrow.is_completed() {
+        return actions;
+    }
+
+    let is_creator = *addr == escrow.creator;
+    if escrow.is_approver(addr)
+        && !is_creator
+        && escrow.is_funded()
+        && !escrow.has_current_approval(addr, now)
+        && !escrow.is_expired(now)
+        && !escrow.disputed
+    {
+        actions.push(EscrowAction::Approve);
     }
+    if escrow.is_beneficiary(addr) && !escrow.accepted_by.contains(addr) {
+        actions.push(EscrowAction::Accept);
+    }
+    if is_creator && !escrow.is_funded() {
+        actions.push(EscrowAction::Fund);
+    }
+
+    // Under two-phase release an approved escrow waits for a party to finalize it
+    let can_finalize = config.two_phase_release
+        && escrow.can_be_released(now)
+        && !escrow.disputed
+        && !escrow.is_time_locked(now)
+        && !matches!(escrow.release_eligible_at, Some(eligible_at) if now < eligible_at);
+    if can_finalize && (is_creator || escrow.is_approver(addr)) {
+        actions.push(EscrowAction::Finalize);
+    }
+    if is_creator && !escrow.has_outside_approvals() && !escrow.disputed {
+        actions.push(EscrowAction::Cancel);
+    }
+
+    actions
+}
+
+fn query_pending_approvals(
+    deps: Deps,
+    env: Env,
+    approver: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    sort: Option<SortKey>,
+) -> StdResult<EscrowListResponse> {
+    let approver = deps.api.addr_validate(&approver)?;
+    let limit = limit.unwrap_or(10) as usize;
+    let now = env.block.time.seconds();
+    let is_pending = |escrow: &Escrow| {
+        !escrow.is_completed() && !escrow.has_current_approval(&approver, now) && escrow.is_eligible(&approver)
+    };
+
+    let escrows = match sort.unwrap_or(SortKey::Oldest) {
+        // `start_after` bounds the range from above when listing the newest escrows first
+        sort @ (SortKey::Oldest | SortKey::Newest) => {
+            let (min, max, order) = match sort {
+                SortKey::Newest => (None, start_after.map(Bound::exclusive), Order::Descending),
+                _ => (start_after.map(Bound::exclusive), None, Order::Ascending),
+            };
+            let mut escrows = vec![];
+            for id in ESCROWS_BY_APPROVER.prefix(&approver).keys(deps.storage, min, max, order) {
+                if escrows.len() >= limit {
+                    break;
+                }
+                let escrow = ESCROWS.load(deps.storage, id?)?;
+                if is_pending(&escrow) {
+                    escrows.push(escrow);
+                }
+            }
+            escrows
+        }
+        // The oldest pending escrows are ranked by the approval weight they still miss, so
+        // the ranking has no stable cursor to page with. The candidates are capped, as is
+        // the part of the index searched for them, and only their keys are kept.
+        SortKey::NearestToRelease => {
+            if start_after.is_some() {
+                return Err(StdError::generic_err("Cannot page through escrows sorted by NearestToRelease"));
+            }
+            let mut ranked = vec![];
+            let ids = ESCROWS_BY_APPROVER
+                .prefix(&approver)
+                .keys(deps.storage, None, None, Order::Ascending)
+                .take(MAX_SORT_SCAN);
+            for id in ids {
+                if ranked.len() == MAX_SORT_CANDIDATES {
+                    break;
+                }
+                let escrow = ESCROWS.load(deps.storage, id?)?;
+                if is_pending(&escrow) {
+                    ranked.push((escrow.release_weight().saturating_sub(escrow.current_weight(now)), escrow.id));
+                }
+            }
+            ranked.sort_unstable();
+            ranked
+                .into_iter()
+                .take(limit)
+                .map(|(_, id)| ESCROWS.load(deps.storage, id))
+                .collect::<StdResult<Vec<_>>>()?
+        }
+    };
+
+    let escrows = escrows
+        .into_iter()
+        .map(|escrow| escrow_to_response(deps.storage, escrow, now))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(EscrowListResponse { escrows })
+}
+
+fn query_escrows_by_denom(
+    deps: Deps,
+    env: Env,
+    denom: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowListResponse> {
+    let limit = limit.unwrap_or(10) as usize;
+
+    let escrows = ESCROWS_BY_DENOM
+        .prefix(&denom)
+        .keys(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|id| escrow_to_response(deps.storage, ESCROWS.load(deps.storage, id?)?, env.block.time.seconds()))
+        .collect::<StdResult<Vec<_>>>()?;
 
     Ok(EscrowListResponse { escrows })
 }
 
 fn query_all_escrows(
     deps: Deps,
+    env: Env,
+    status: Option<EscrowStatus>,
+    include_completed: bool,
     start_after: Option<u64>,
     limit: Option<u32>,
+    order: Option<SortOrder>,
 ) -> StdResult<EscrowListResponse> {
     let limit = limit.unwrap_or(10) as usize;
-    let start = start_after.map(|s| Bound::exclusive(s));
+    let order = order.map_or(Order::Ascending, Order::from);
+
+    // `start_after` bounds the range from below when ascending and from above when descending
+    let (min, max) = match order {
+        Order::Ascending => (start_after.map(Bound::exclusive), None),
+        Order::Descending => (None, start_after.map(Bound::exclusive)),
+    };
 
+    // Always page over `ESCROWS` rather than the role indexes: entries are never removed from it,
+    // so the cursor cannot skip or repeat escrows that change status between pages
     let escrows: StdResult<Vec<_>> = ESCROWS
-        .range(deps.storage, start, None, Order::Ascending)
+        .range(deps.storage, min, max, order)
+        .filter(|item| match item {
+            Ok((_, escrow)) => {
+                matches_status(escrow, &status) && (include_completed || escrow.status == EscrowStatus::Open)
+            }
+            Err(_) => true,
+        })
         .take(limit)
         .map(|item| {
             let (_, escrow) = item?;
-            Ok(escrow_to_response(escrow))
+            escrow_to_response(deps.storage, escrow, env.block.time.seconds())
+        })
+        .collect();
+
+    Ok(EscrowListResponse { escrows: escrows? })
+}
+
+fn query_escrows_by_time_range(
+    deps: Deps,
+    env: Env,
+    start_time: u64,
+    end_time: u64,
+    start_after: Option<(u64, u64)>,
+    limit: Option<u32>,
+) -> StdResult<EscrowListResponse> {
+    let limit = limit.unwrap_or(10) as usize;
+    let start = (start_time, 0);
+    let end = (end_time, u64::MAX);
+
+    // Nothing left to return for an empty range or a cursor past its end
+    if start > end || matches!(start_after, Some(cursor) if cursor >= end) {
+        return Ok(EscrowListResponse { escrows: vec![] });
+    }
+
+    let min = match start_after {
+        Some(cursor) if cursor >= start => Bound::exclusive(cursor),
+        _ => Bound::inclusive(start),
+    };
+
+    let escrows: StdResult<Vec<_>> = ESCROWS_BY_CREATED_AT
+        .keys(deps.storage, Some(min), Some(Bound::inclusive(end)), Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, id) = item?;
+            escrow_to_response(deps.storage, ESCROWS.load(deps.storage, id)?, env.block.time.seconds())
         })
         .collect();
 
     Ok(EscrowListResponse { escrows: escrows? })
 }
 
-fn escrow_to_response(escrow: Escrow) -> EscrowResponse {
-    EscrowResponse {
+fn query_escrow_history(
+    deps: Deps,
+    escrow_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowHistoryResponse> {
+    let limit = limit.unwrap_or(10) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let events = ESCROW_HISTORY
+        .prefix(escrow_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (sequence, event) = item?;
+            Ok(EscrowEventResponse {
+                sequence,
+                kind: event.kind,
+                actor: event.actor,
+                timestamp: event.timestamp,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(EscrowHistoryResponse { events })
+}
+
+fn matches_status(escrow: &Escrow, status: &Option<EscrowStatus>) -> bool {
+    match status {
+        Some(status) => escrow.status == *status,
+        None => true,
+    }
+}
+
+fn escrow_to_response(storage: &dyn Storage, escrow: Escrow, now: u64) -> StdResult<EscrowResponse> {
+    // Aliases only change what is displayed, the coins keep their real denoms
+    let mut has_alias = false;
+    let mut display = Vec::with_capacity(escrow.amount.len());
+    for coin in escrow.amount.iter() {
+        match DENOM_ALIASES.may_load(storage, &coin.denom)? {
+            Some(alias) => {
+                has_alias = true;
+                display.push(alias);
+            }
+            None => display.push(coin.denom.clone()),
+        }
+    }
+    let denom_display = has_alias.then(|| display.join(","));
+
+    let threshold = escrow.required_approvals() as u32;
+    let required_weight = escrow.release_weight();
+    let approver_weights = escrow.approvers.iter().map(|addr| escrow.approver_weight(addr)).collect();
+    let funded = escrow.is_funded();
+    let accepted = escrow.is_accepted();
+    let ready_to_release = escrow.can_be_released(now);
+    Ok(EscrowResponse {
         id: escrow.id,
         creator: escrow.creator,
-        beneficiary: escrow.beneficiary,
+        beneficiaries: escrow.beneficiaries,
         amount: escrow.amount,
-        approver1: escrow.approver1,
-        approver2: escrow.approver2,
-        approver3: escrow.approver3,
+        denom_display,
+        approvers: escrow.approvers,
         description: escrow.description,
         approvals: escrow.approvals,
-        is_completed: escrow.is_completed,
+        cancel_votes: escrow.cancel_votes,
+        threshold,
+        status: escrow.status,
         created_at: escrow.created_at,
         completed_at: escrow.completed_at,
-    }
+        expires_at: escrow.expires_at,
+        release_after: escrow.release_after,
+        arbiter: escrow.arbiter,
+        disputed: escrow.disputed,
+        funded,
+        accepted,
+        ready_to_release,
+        accepted_by: escrow.accepted_by,
+        accept_deadline: escrow.accept_deadline,
+        settlement: escrow.settlement,
+        beneficiary_change: escrow.beneficiary_change,
+        depends_on: escrow.depends_on,
+        auto_approve_creator: escrow.auto_approve_creator,
+        metadata: escrow.metadata,
+        release_eligible_at: escrow.release_eligible_at,
+        payout_overrides: escrow.payout_overrides,
+        policy: escrow.policy,
+        approval_ttl: escrow.approval_ttl,
+        approver_weights,
+        required_weight,
+        release_hook: escrow.release_hook,
+        release_hook_msg: escrow.release_hook_msg,
+        remainder_policy: escrow.remainder_policy,
+    })
 }
 
-fn update_escrow_indexes(
-    storage: &mut dyn cosmwasm_std::Storage,
-    escrow: &Escrow,
-    add: bool,
+/// Appends an event to the escrow's history, after the last recorded one
+fn record_history(
+    storage: &mut dyn Storage,
+    escrow_id: u64,
+    kind: EscrowEventKind,
+    actor: &Addr,
+    timestamp: u64,
 ) -> StdResult<()> {
-    // Update creator index
-    ESCROWS_BY_CREATOR.update(storage, &escrow.creator, |existing| -> StdResult<Vec<u64>> {
-        let mut ids = existing.unwrap_or_default();
-        if add {
-            ids.push(escrow.id);
-        } else {
-            ids.retain(|&id| id != escrow.id);
-        }
-        Ok(ids)
-    })?;
+    let sequence = match ESCROW_HISTORY
+        .prefix(escrow_id)
+        .keys(storage, None, None, Order::Descending)
+        .next()
+    {
+        Some(last) => last? + 1,
+        None => 0,
+    };
+    let event = EscrowEvent { kind, actor: actor.clone(), timestamp };
+    ESCROW_HISTORY.save(storage, (escrow_id, sequence), &event)
+}
+
+/// Counts a newly created escrow and adds its funds to the total locked
+fn record_escrow_opened(storage: &mut dyn Storage, amount: &[Coin]) -> StdResult<()> {
+    let mut stats = STATS.load(storage)?;
+    stats.total_escrows += 1;
+    stats.open_count += 1;
+    STATS.save(storage, &stats)?;
+
+    lock_funds(storage, amount)
+}
+
+/// Adds fees to those held for the fee recipient
+fn accrue_fees(storage: &mut dyn Storage, fee: &[Coin]) -> StdResult<()> {
+    for coin in fee {
+        FEE_ACCRUED.update(storage, &coin.denom, |accrued| -> StdResult<_> {
+            Ok(accrued.unwrap_or_default() + coin.amount)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Adds funds entering escrow to the total locked
+fn lock_funds(storage: &mut dyn Storage, amount: &[Coin]) -> StdResult<()> {
+    for coin in amount {
+        TOTAL_LOCKED.update(storage, &coin.denom, |locked| -> StdResult<_> {
+            Ok(locked.unwrap_or_default() + coin.amount)
+        })?;
+    }
+
+    Ok(())
+}
 
-    // Update beneficiary index
-    ESCROWS_BY_BENEFICIARY.update(storage, &escrow.beneficiary, |existing| -> StdResult<Vec<u64>> {
-        let mut ids = existing.unwrap_or_default();
-        if add {
-            ids.push(escrow.id);
+/// Counts an escrow leaving the open state with the given status and removes its funds
+/// from the total locked
+fn record_escrow_completed(storage: &mut dyn Storage, status: &EscrowStatus, amount: &[Coin]) -> StdResult<()> {
+    let mut stats = STATS.load(storage)?;
+    stats.open_count -= 1;
+    stats.completed_count += 1;
+    *stats.status_count_mut(status) += 1;
+    STATS.save(storage, &stats)?;
+
+    for coin in amount {
+        let locked = TOTAL_LOCKED.load(storage, &coin.denom)?.checked_sub(coin.amount)?;
+        if locked.is_zero() {
+            TOTAL_LOCKED.remove(storage, &coin.denom);
         } else {
-            ids.retain(|&id| id != escrow.id);
+            TOTAL_LOCKED.save(storage, &coin.denom, &locked)?;
         }
-        Ok(ids)
-    })?;
-
-    // Update approver indexes (avoid duplicate updates for the same address)
-    let mut unique_approvers: Vec<&cosmwasm_std::Addr> = vec![&escrow.approver1, &escrow.approver2];
-    if let Some(ref approver3) = escrow.approver3 {
-        unique_approvers.push(approver3);
     }
-    unique_approvers.sort();
-    unique_approvers.dedup();
 
-    for approver in unique_approvers.into_iter() {
-        ESCROWS_BY_APPROVER.update(storage, approver, |existing| -> StdResult<Vec<u64>> {
-            let mut ids = existing.unwrap_or_default();
-            if add {
-                if !ids.contains(&escrow.id) {
-                    ids.push(escrow.id);
+    Ok(())
+}
+
+/// Undoes `record_escrow_completed` for an escrow whose release failed
+fn record_escrow_reopened(storage: &mut dyn Storage, amount: &[Coin]) -> StdResult<()> {
+    let mut stats = STATS.load(storage)?;
+    stats.open_count += 1;
+    stats.completed_count -= 1;
+    stats.released_count -= 1;
+    STATS.save(storage, &stats)?;
+
+    lock_funds(storage, amount)
+}
+
+/// Splits the payout between the beneficiaries by weight. Shares are rounded down and the
+/// leftover of each coin, less than one unit per beneficiary, is handed out following the
+/// remainder policy, so the shares always add up to exactly the payout. Beneficiaries whose
+/// share is zero in every denom are left out.
+fn split_shares(payout: &[Coin], beneficiaries: &[(Addr, u64)], policy: &RemainderPolicy) -> Vec<(Addr, Vec<Coin>)> {
+    let total_weight: u128 = beneficiaries.iter().map(|(_, weight)| *weight as u128).sum();
+    let mut shares: Vec<(Addr, Vec<Coin>)> = beneficiaries
+        .iter()
+        .map(|(beneficiary, _)| (beneficiary.clone(), vec![]))
+        .collect();
+
+    for coin in payout {
+        let mut amounts: Vec<Uint128> = beneficiaries
+            .iter()
+            .map(|(_, weight)| coin.amount.multiply_ratio(*weight as u128, total_weight))
+            .collect();
+        let leftover = amounts.iter().fold(coin.amount, |left, amount| left - *amount);
+
+        match policy {
+            RemainderPolicy::FirstBeneficiary => amounts[0] += leftover,
+            RemainderPolicy::LastBeneficiary => *amounts.last_mut().unwrap() += leftover,
+            RemainderPolicy::ProRataLargest => {
+                // Largest remainder method: the shares rounded down the most get one unit
+                // each, earlier beneficiaries first on ties
+                let total = Uint256::from(total_weight);
+                let mut order: Vec<(Uint256, usize)> = beneficiaries
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, weight))| {
+                        let exact = Uint256::from(coin.amount) * Uint256::from(*weight);
+                        (exact.checked_rem(total).unwrap_or_default(), i)
+                    })
+                    .collect();
+                order.sort_by(|(a, i), (b, j)| b.cmp(a).then(i.cmp(j)));
+                for (_, i) in order.into_iter().take(leftover.u128() as usize) {
+                    amounts[i] += Uint128::one();
                 }
-            } else {
-                ids.retain(|&id| id != escrow.id);
             }
-            Ok(ids)
-        })?;
+        }
+
+        for (share, amount) in shares.iter_mut().zip(amounts) {
+            if !amount.is_zero() {
+                share.1.push(Coin { denom: coin.denom.clone(), amount });
+            }
+        }
+    }
+
+    shares.retain(|(_, share)| !share.is_empty());
+    shares
+}
+
+/// Splits the amount into the beneficiary payout and the platform fee. Fees are rounded
+/// as configured, and the payout is what is left, so the payout and fee always add up to
+/// exactly the original amount. Zero coins are left out of both halves.
+fn split_fee(amount: &[Coin], fee_bps: u16, rounding: &FeeRounding) -> (Vec<Coin>, Vec<Coin>) {
+    let mut payout = vec![];
+    let mut fee = vec![];
+    for coin in amount {
+        let fee_amount = fee_amount(coin.amount, fee_bps, rounding);
+        let payout_amount = coin.amount - fee_amount;
+        if !payout_amount.is_zero() {
+            payout.push(Coin::new(payout_amount.u128(), coin.denom.clone()));
+        }
+        if !fee_amount.is_zero() {
+            fee.push(Coin::new(fee_amount.u128(), coin.denom.clone()));
+        }
+    }
+    (payout, fee)
+}
+
+/// `fee_bps` basis points of the amount, rounded to a whole unit. With at most 10000 basis
+/// points the fee never exceeds the amount, whatever the rounding.
+fn fee_amount(amount: Uint128, fee_bps: u16, rounding: &FeeRounding) -> Uint128 {
+    let scaled = Uint256::from(amount) * Uint256::from(fee_bps);
+    let divisor = Uint256::from(MAX_FEE_BPS);
+    let (quotient, remainder) = (scaled / divisor, scaled % divisor);
+
+    let round_up = match rounding {
+        FeeRounding::Floor => false,
+        FeeRounding::Ceil => !remainder.is_zero(),
+        FeeRounding::HalfEven => {
+            let twice = remainder * Uint256::from(2u8);
+            twice > divisor || (twice == divisor && quotient % Uint256::from(2u8) == Uint256::one())
+        }
+    };
+    let fee = if round_up { quotient + Uint256::one() } else { quotient };
+    // The fee is at most the amount, so it always fits
+    Uint128::try_from(fee).unwrap_or(amount)
+}
+
+fn denoms_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|coin| coin.denom.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn beneficiaries_to_string(escrow: &Escrow) -> String {
+    escrow
+        .beneficiaries
+        .iter()
+        .map(|(beneficiary, _)| beneficiary.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Starts an indexer event of the given type for the escrow
+fn escrow_event(ty: &str, escrow: &Escrow) -> Event {
+    Event::new(ty).add_attribute("escrow_id", escrow.id.to_string())
+}
+
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Adds the escrow to the address and denom indexes. Escrows stay in them once their
+/// status is final, so an address's history remains queryable with a status filter.
+fn update_escrow_indexes(storage: &mut dyn Storage, escrow: &Escrow) -> StdResult<()> {
+    let id = escrow.id;
+    ESCROWS_BY_CREATOR.save(storage, (&escrow.creator, id), &())?;
+    for (beneficiary, _) in escrow.beneficiaries.iter() {
+        ESCROWS_BY_BENEFICIARY.save(storage, (beneficiary, id), &())?;
+    }
+    for approver in escrow.approvers.iter() {
+        ESCROWS_BY_APPROVER.save(storage, (approver, id), &())?;
+    }
+    for coin in escrow.amount.iter() {
+        ESCROWS_BY_DENOM.save(storage, (&coin.denom, id), &())?;
     }
 
     Ok(())
 }
 
 #[entry_point]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+
+    // Only upgrade this contract, and never to an older version. Migrating again to the
+    // same version is a no-op as the data steps below only run for older versions.
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrateContract { contract: stored.contract });
+    }
+    if version_parts(&stored.version)? > version_parts(CONTRACT_VERSION)? {
+        return Err(ContractError::CannotMigrateVersion { version: stored.version });
+    }
+
+    // Convert escrows stored in an older layout
+    match stored.version.as_str() {
+        // v0.1.0 stored a single coin, three approver slots and an `is_completed` flag
+        "0.1.0" => migrate_escrows(deps.storage, ESCROWS_V1)?,
+        // v0.2.0 and v0.3.0 recorded approvals without a timestamp
+        "0.2.0" | "0.3.0" => migrate_escrows(deps.storage, ESCROWS_V2)?,
+        // v0.4.0 had a single beneficiary
+        "0.4.0" => migrate_escrows(deps.storage, ESCROWS_V4)?,
+        _ => {}
+    }
+
+    // v0.2.0 configs lack later fields; rewrite them with the defaults filled in
+    if stored.version == "0.2.0" {
+        if let Some(config) = CONFIG.may_load(deps.storage)? {
+            CONFIG.save(deps.storage, &config)?;
+        }
+    }
+
+    // v0.1.0 and early v0.2.0 builds had no config; hand the admin role to the contract's
+    // wasm admin
+    if matches!(stored.version.as_str(), "0.1.0" | "0.2.0") && !CONFIG.exists(deps.storage) {
+        let contract_info = deps.querier.query_wasm_contract_info(&env.contract.address)?;
+        let admin = contract_info.admin.unwrap_or(contract_info.creator);
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                admin: deps.api.addr_validate(&admin)?,
+                paused: false,
+                fee_recipient: None,
+                fee_bps: 0,
+                cancel_fee_bps: 0,
+                require_acceptance: false,
+                max_description_length: DEFAULT_MAX_DESCRIPTION_LENGTH,
+                min_amounts: vec![],
+                allowed_denoms: None,
+                block_beneficiary_approver: false,
+                two_phase_release: false,
+                grace_period: 0,
+                creator_allowlist_enabled: false,
+                rate_limit: None,
+                default_threshold_policy: None,
+                approval_tiers: vec![],
+                fee_rounding: FeeRounding::Floor,
+            },
+        )?;
+    }
+
+    // Up to v0.2.0 the address indexes were one list of IDs per address
+    // and there were no running stats
+    if stored.version == "0.1.0" || stored.version == "0.2.0" {
+        rebuild_escrow_indexes(deps.storage)?;
+        rebuild_stats(deps.storage)?;
+    }
+
+    // The creation time index was added in v0.6.0
+    if matches!(stored.version.as_str(), "0.1.0" | "0.2.0" | "0.3.0" | "0.4.0" | "0.5.0") {
+        build_created_at_index(deps.storage)?;
+    }
+
+    // Before v0.7.0 the required approvals were worked out on every use
+    if version_parts(&stored.version)? < vec![0, 7, 0] {
+        freeze_required_approvals(deps.storage)?;
+    }
+
+    // Before v0.8.0 cancelled and expired escrows were dropped from the address indexes,
+    // there was no denom or expiration index, and the stats did not count each status
+    if version_parts(&stored.version)? < vec![0, 8, 0] {
+        reindex_escrows(deps.storage)?;
+        build_expires_at_index(deps.storage)?;
+        count_completed_by_status(deps.storage)?;
+    }
+
     // Update stored contract version for future migrations
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(Response::new()
@@ -386,3 +3854,125 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
         .add_attribute("contract_name", CONTRACT_NAME)
         .add_attribute("contract_version", CONTRACT_VERSION))
 }
+
+/// Numeric parts of a `major.minor.patch` version, ordered like the versions they represent
+fn version_parts(version: &str) -> Result<Vec<u64>, ContractError> {
+    version
+        .split('.')
+        .map(|part| part.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| ContractError::CannotMigrateVersion { version: version.to_string() })
+}
+
+/// Rewrites every escrow stored in an older layout in the current one
+fn migrate_escrows<T>(storage: &mut dyn Storage, old_escrows: Map<u64, T>) -> StdResult<()>
+where
+    T: Serialize + DeserializeOwned,
+    Escrow: From<T>,
+{
+    let old_escrows = old_escrows
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (id, old) in old_escrows {
+        ESCROWS.save(storage, id, &Escrow::from(old))?;
+    }
+
+    Ok(())
+}
+
+fn rebuild_escrow_indexes(storage: &mut dyn Storage) -> StdResult<()> {
+    LEGACY_ESCROWS_BY_CREATOR.clear(storage);
+    LEGACY_ESCROWS_BY_BENEFICIARY.clear(storage);
+    LEGACY_ESCROWS_BY_APPROVER.clear(storage);
+
+    reindex_escrows(storage)
+}
+
+fn freeze_required_approvals(storage: &mut dyn Storage) -> StdResult<()> {
+    let escrows = ESCROWS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (id, mut escrow) in escrows {
+        if escrow.required_approvals == 0 {
+            escrow.required_approvals = escrow.rule_required_approvals() as u32;
+            ESCROWS.save(storage, id, &escrow)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn build_created_at_index(storage: &mut dyn Storage) -> StdResult<()> {
+    let escrows = ESCROWS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (id, escrow) in escrows {
+        ESCROWS_BY_CREATED_AT.save(storage, (escrow.created_at, id), &())?;
+    }
+
+    Ok(())
+}
+
+/// Counts the completed escrows by status, which the stats did not track before v0.8.0
+fn count_completed_by_status(storage: &mut dyn Storage) -> StdResult<()> {
+    let mut stats = match STATS.may_load(storage)? {
+        Some(stats) => stats,
+        None => return Ok(()),
+    };
+    stats.released_count = 0;
+    stats.cancelled_count = 0;
+    stats.expired_count = 0;
+    for item in ESCROWS.range(storage, None, None, Order::Ascending) {
+        let (_, escrow) = item?;
+        if escrow.is_completed() {
+            *stats.status_count_mut(&escrow.status) += 1;
+        }
+    }
+
+    STATS.save(storage, &stats)
+}
+
+fn build_expires_at_index(storage: &mut dyn Storage) -> StdResult<()> {
+    let escrows = ESCROWS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (id, escrow) in escrows {
+        if let (Some(expires_at), false) = (escrow.expires_at, escrow.is_completed() || escrow.disputed) {
+            ESCROWS_BY_EXPIRES_AT.save(storage, (expires_at, id), &())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn reindex_escrows(storage: &mut dyn Storage) -> StdResult<()> {
+    let escrows = ESCROWS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (_, escrow) in escrows {
+        update_escrow_indexes(storage, &escrow)?;
+    }
+
+    Ok(())
+}
+
+fn rebuild_stats(storage: &mut dyn Storage) -> StdResult<()> {
+    let escrows = ESCROWS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    STATS.save(storage, &Stats::default())?;
+    for (_, escrow) in escrows {
+        record_escrow_opened(storage, &escrow.amount)?;
+        if escrow.is_completed() {
+            record_escrow_completed(storage, &escrow.status, &escrow.amount)?;
+        }
+    }
+
+    Ok(())
+}
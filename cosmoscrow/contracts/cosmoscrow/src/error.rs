@@ -9,27 +9,186 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Sender is not an approver of this escrow")]
+    NotAnApprover {},
+
     #[error("Escrow not found")]
     EscrowNotFound {},
 
+    #[error("Public key must be a 33 or 65 byte secp256k1 key")]
+    InvalidPublicKey {},
+
+    #[error("Signature does not match the approver's registered key")]
+    InvalidSignature {},
+
+    #[error("Template not found")]
+    TemplateNotFound {},
+
+    #[error("No approval delegate is set")]
+    NoDelegate {},
+
     #[error("Escrow already completed")]
     EscrowCompleted {},
 
     #[error("Insufficient funds sent")]
     InsufficientFunds {},
 
-    #[error("Invalid beneficiary address")]
+    #[error("Escrow was already accepted")]
+    AlreadyAccepted {},
+
+    #[error("Settlement does not match the escrowed funds")]
+    InvalidSettlement {},
+
+    #[error("Denom is not allowed for escrows")]
+    DenomNotAllowed {},
+
+    #[error("Escrow amount is below the minimum")]
+    BelowMinimum {},
+
+    #[error("Escrow is not funded yet")]
+    NotFunded {},
+
+    #[error("Escrow is already funded")]
+    AlreadyFunded {},
+
+    #[error("Funds cannot be sent with this message")]
+    UnexpectedFunds {},
+
+    #[error("Sent denom is not held by the escrow")]
+    DenomMismatch {},
+
+    #[error("Invalid beneficiaries: between 1 and 10 unique addresses with non-zero weights")]
     InvalidBeneficiary {},
 
     #[error("Invalid approver address")]
     InvalidApprover {},
 
+    #[error("The contract cannot be an escrow's creator")]
+    InvalidCreator {},
+
+    #[error("Release hook needs both a contract and a message, and cannot be this contract")]
+    InvalidReleaseHook {},
+
+    #[error("Beneficiary cannot be an approver")]
+    BeneficiaryCannotApprove {},
+
+    #[error("Duplicate approver")]
+    DuplicateApprover {},
+
+    #[error("Too many approvers")]
+    TooManyApprovers {},
+
+    #[error("An escrow needs at least one approver and one required approval")]
+    NoApprovers {},
+
     #[error("Approver already approved")]
     AlreadyApproved {},
 
+    #[error("Approver has not approved")]
+    NotApproved {},
+
     #[error("Cannot approve your own escrow as the creator")]
     CannotSelfApprove {},
 
+    #[error("Approval threshold must be between 1 and the number of approvers")]
+    InvalidThreshold {},
+
+    #[error("Approvers do not match the approval policy")]
+    PolicyMismatch {},
+
+    #[error("Approver weights must be non-zero, one per approver, and reach the required weight")]
+    InvalidWeights {},
+
+    #[error("Fee must be at most 10000 basis points and requires a fee recipient")]
+    InvalidFee {},
+
+    #[error("Approval tiers need a denom and a non-zero minimum amount")]
+    InvalidApprovalTier {},
+
+    #[error("Too many escrows created, try again later")]
+    RateLimited {},
+
+    #[error("Rate limit needs a non-zero window and number of creations")]
+    InvalidRateLimit {},
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Description is too long")]
+    DescriptionTooLong {},
+
+    #[error("Comment is too long")]
+    CommentTooLong {},
+
+    #[error("Metadata too large")]
+    MetadataTooLarge {},
+
+    #[error("Contract balance does not cover the escrowed funds")]
+    InsufficientContractBalance {},
+
+    #[error("Escrow has not been accepted by all beneficiaries")]
+    NotAccepted {},
+
+    #[error("Escrow is disputed")]
+    Disputed {},
+
+    #[error("Escrow is not disputed")]
+    NotDisputed {},
+
+    #[error("Escrow has no arbiter")]
+    NoArbiter {},
+
+    #[error("Too many escrows in one batch")]
+    BatchTooLarge {},
+
+    #[error("Escrow counter overflow")]
+    CounterOverflow {},
+
     #[error("Escrow conditions not met for release")]
     ConditionsNotMet {},
+
+    #[error("Escrow has expired")]
+    EscrowExpired {},
+
+    #[error("Escrow cannot be released yet")]
+    TooEarly {},
+
+    #[error("Escrow has not expired yet")]
+    EscrowNotExpired {},
+
+    #[error("Expiration must be in the future and after the release time")]
+    InvalidExpiration {},
+
+    #[error("Cannot migrate from contract {contract}")]
+    CannotMigrateContract { contract: String },
+
+    #[error("Cannot migrate from version {version}")]
+    CannotMigrateVersion { version: String },
+
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("Ownership transfer has expired")]
+    OwnershipTransferExpired {},
+
+    #[error("No emergency withdrawal is pending for this escrow")]
+    NoEmergencyWithdraw {},
+
+    #[error("A beneficiary change is pending for this escrow")]
+    BeneficiaryChangePending {},
+
+    #[error("No beneficiary change is pending for this escrow")]
+    NoBeneficiaryChange {},
+
+    #[error("Escrow this one depends on is not released")]
+    DependencyNotReleased {},
+
+    #[error("Sent funds do not match the expected amount")]
+    AmountMismatch {},
+
+    #[error("No fees accrued in this denom")]
+    NoFeesAccrued {},
+
+    #[error("Emergency withdrawal cannot be executed before {executable_at}")]
+    EmergencyWithdrawLocked { executable_at: u64 },
 }
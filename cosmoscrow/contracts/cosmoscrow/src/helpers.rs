@@ -2,10 +2,17 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{
-    to_json_binary, Addr, CosmosMsg, CustomQuery, Querier, QuerierWrapper, StdResult, WasmMsg,
+    to_json_binary, Addr, Coin, CosmosMsg, CustomQuery, Querier, QuerierWrapper, StdResult, WasmMsg,
 };
 
-use crate::msg::{ExecuteMsg, QueryMsg};
+use crate::msg::{CreateEscrowMsg, ExecuteMsg, QueryMsg};
+
+/// Message an approver signs for `ApproveReleaseSigned`, binding the approval to the
+/// escrow, to this contract and to the approver's current nonce (see `GetApprovalNonce`).
+/// The signature is over the SHA-256 hash of these bytes.
+pub fn approval_sign_bytes(contract: &Addr, escrow_id: u64, approver: &Addr, nonce: u64) -> Vec<u8> {
+    format!("cosmoscrow:approve_release:{}:{}:{}:{}", contract, escrow_id, approver, nonce).into_bytes()
+}
 
 /// CwTemplateContract is a wrapper around Addr that provides a lot of helpers
 /// for working with this.
@@ -18,15 +25,35 @@ impl CwTemplateContract {
     }
 
     pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
+        self.call_with_funds(msg, vec![])
+    }
+
+    /// Like `call`, but attaches the given coins to the execution
+    pub fn call_with_funds<T: Into<ExecuteMsg>>(&self, msg: T, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
         let msg = to_json_binary(&msg.into())?;
         Ok(WasmMsg::Execute {
             contract_addr: self.addr().into(),
             msg,
-            funds: vec![],
+            funds,
         }
         .into())
     }
 
+    /// Create an escrow locking the attached funds
+    pub fn create_escrow(&self, msg: CreateEscrowMsg, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        self.call_with_funds(ExecuteMsg::CreateEscrow(msg), funds)
+    }
+
+    /// Approve the release of an escrow
+    pub fn approve_release(&self, escrow_id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ApproveRelease { escrow_id, comment: None })
+    }
+
+    /// Cancel an escrow
+    pub fn cancel_escrow(&self, escrow_id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::CancelEscrow { escrow_id })
+    }
+
     /// Get Custom
     pub fn query<Q, T, CQ>(&self, querier: &Q, msg: QueryMsg) -> StdResult<T>
     where
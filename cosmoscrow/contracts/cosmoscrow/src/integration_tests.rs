@@ -1,17 +1,30 @@
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_json, Addr, Coin};
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        coin, coins, from_json, to_json_binary, Addr, Attribute, BankMsg, Binary, Coin, ContractResult, CosmosMsg, Deps,
+        Int256, OwnedDeps, Reply, ReplyOn, Response, SubMsgResult, SystemError, SystemResult, Uint128, WasmMsg,
+        WasmQuery,
+    };
 
-    use crate::contract::{execute, instantiate, query};
-    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, EscrowResponse};
+    use crate::contract::{execute, instantiate, migrate, query, reply};
+    use crate::helpers::CwTemplateContract;
+    use crate::msg::{AccruedFeesResponse, ActionableResponse, ApprovalListResponse, ApprovalStatusResponse, CanCancelResponse, CapabilitiesResponse, ConfigResponse, CreateEscrowMsg, EscrowAction, EscrowRole, ExecuteMsg, InstantiateMsg, InvariantReport, MigrateMsg, OwnershipAction, OwnershipResponse, QueryMsg, ReconciliationResponse, SortKey, SortOrder, TemplateListResponse, EscrowHistoryResponse, EscrowListResponse, EscrowResponse, RoleResponse, StatsResponse};
+    use crate::state::{Approval, ApprovalMode, ApprovalPolicy, ApprovalTier, EscrowEventKind, EscrowStatus, FeeRounding, RateLimit, RemainderPolicy, EscrowV1, EscrowV2, ESCROWS, ESCROWS_BY_APPROVER, ESCROWS_BY_BENEFICIARY, ESCROWS_BY_CREATOR, ESCROWS_BY_DENOM, ESCROWS_BY_EXPIRES_AT, ESCROW_COUNTER, ESCROWS_V1, ESCROWS_V2};
     use crate::ContractError;
 
+    /// Mock dependencies whose contract holds enough funds to pay out the test escrows
+    fn mock_funded_dependencies() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        mock_dependencies_with_balance(&[Coin::new(1_000_000, "ujuno"), Coin::new(1_000_000, "uusdc")])
+    }
+
     #[test]
     fn proper_initialization() {
         let mut deps = mock_dependencies();
 
-        let msg = InstantiateMsg {};
+        let msg = InstantiateMsg::default();
         let info = mock_info("creator", &coins(1000, "earth"));
 
         let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -21,19 +34,18 @@ mod tests {
     #[test]
     fn create_escrow_success() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {};
+        let msg = InstantiateMsg::default();
         let info = mock_info("creator", &coins(2, "token"));
         let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Create escrow
         let info = mock_info("creator", &coins(1000, "ujuno"));
-        let msg = ExecuteMsg::CreateEscrow {
-            beneficiary: "beneficiary".to_string(),
-            approver1: "approver1".to_string(),
-            approver2: "approver2".to_string(),
-            approver3: Some("approver3".to_string()),
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
             description: "Test escrow".to_string(),
-        };
+            ..Default::default()
+        });
 
         let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(res.attributes.len(), 6);
@@ -44,78 +56,153 @@ mod tests {
     #[test]
     fn create_escrow_insufficient_funds() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {};
+        let msg = InstantiateMsg::default();
         let info = mock_info("creator", &coins(2, "token"));
         let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Try to create escrow without funds
         let info = mock_info("creator", &[]);
-        let msg = ExecuteMsg::CreateEscrow {
-            beneficiary: "beneficiary".to_string(),
-            approver1: "approver1".to_string(),
-            approver2: "approver2".to_string(),
-            approver3: None,
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
             description: "Test escrow".to_string(),
-        };
+            ..Default::default()
+        });
 
         let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
         assert!(matches!(err, ContractError::InsufficientFunds {}));
     }
 
+    #[test]
+    fn create_escrow_multiple_coins() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Create escrow holding two denominations
+        let funds = vec![Coin::new(1000, "ujuno"), Coin::new(500, "uusdc")];
+        let info = mock_info("creator", &funds);
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes[4].value, "1000ujuno,500uusdc");
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.amount, funds);
+
+        // Both approvals release the full bundle in a single send
+        let info = mock_info("approver1", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("approver2", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: funds,
+            })
+        );
+    }
+
     #[test]
     fn approve_release_success() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {};
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
         let info = mock_info("creator", &coins(2, "token"));
         let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Create escrow
         let info = mock_info("creator", &coins(1000, "ujuno"));
-        let msg = ExecuteMsg::CreateEscrow {
-            beneficiary: "beneficiary".to_string(),
-            approver1: "creator".to_string(),
-            approver2: "approver2".to_string(),
-            approver3: Some("approver3".to_string()),
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["creator".to_string(), "approver2".to_string(), "approver3".to_string()],
             description: "Test escrow".to_string(),
-        };
+            ..Default::default()
+        });
         let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // First approval (should fail - creator cannot self-approve)
         let info = mock_info("creator", &[]);
-        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1 };
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
         let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
         assert!(matches!(err, ContractError::CannotSelfApprove {}));
 
         // First approval from approver2
         let info = mock_info("approver2", &[]);
-        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1 };
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
         let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(res.attributes[0].value, "approve_release");
         assert_eq!(res.attributes[3].value, "1"); // total_approvals
 
         // Second approval from approver3 - should trigger release
         let info = mock_info("approver3", &[]);
-        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1 };
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
         let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(res.messages.len(), 1); // Bank message to send funds
     }
 
+    #[test]
+    fn creator_cannot_self_approve() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // The creator cannot be the only approver
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["creator".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidApprover {}));
+
+        // With the creator as one of two approvers, the other approver alone releases
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["creator".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::CannotSelfApprove {}));
+
+        let info = mock_info("approver2", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
     #[test]
     fn query_escrow() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {};
+        let msg = InstantiateMsg::default();
         let info = mock_info("creator", &coins(2, "token"));
         let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Create escrow
         let info = mock_info("creator", &coins(1000, "ujuno"));
-        let msg = ExecuteMsg::CreateEscrow {
-            beneficiary: "beneficiary".to_string(),
-            approver1: "approver1".to_string(),
-            approver2: "approver2".to_string(),
-            approver3: None,
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
             description: "Test escrow".to_string(),
-        };
+            ..Default::default()
+        });
         let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Query escrow
@@ -124,10 +211,4919 @@ mod tests {
         
         assert_eq!(escrow.id, 1);
         assert_eq!(escrow.creator, Addr::unchecked("creator"));
-        assert_eq!(escrow.beneficiary, Addr::unchecked("beneficiary"));
-        assert_eq!(escrow.amount, Coin::new(1000, "ujuno"));
+        assert_eq!(escrow.beneficiaries, vec![(Addr::unchecked("beneficiary"), 1)]);
+        assert_eq!(escrow.amount, vec![Coin::new(1000, "ujuno")]);
         assert_eq!(escrow.description, "Test escrow");
-        assert!(!escrow.is_completed);
+        assert_eq!(escrow.status, EscrowStatus::Open);
         assert_eq!(escrow.approvals.len(), 0);
     }
+
+    #[test]
+    fn approve_after_expiry_rejected() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let env = mock_env();
+        let expires_at = env.block.time.seconds() + 100;
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            expires_at: Some(expires_at),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // Approving right at the expiration time still works
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let info = mock_info("approver1", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // One second later the escrow has expired
+        env.block.time = env.block.time.plus_seconds(1);
+        let info = mock_info("approver2", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowExpired {}));
+    }
+
+    #[test]
+    fn refund_expired() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // An expiration in the past is rejected
+        let env = mock_env();
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let mut create = CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            expires_at: Some(env.block.time.seconds()),
+            ..Default::default()
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::CreateEscrow(create.clone())).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidExpiration {}));
+
+        create.expires_at = Some(env.block.time.seconds() + 100);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::CreateEscrow(create)).unwrap();
+
+        // Refund before expiry is rejected
+        let info = mock_info("anyone", &[]);
+        let msg = ExecuteMsg::RefundExpired { escrow_id: 1 };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowNotExpired {}));
+
+        // After expiry anyone can refund the creator
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(101);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Expired);
+
+        // A second refund is not possible
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowCompleted {}));
+    }
+
+    #[test]
+    fn explicit_threshold() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let mut create = CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        };
+
+        // Out of range thresholds are rejected
+        for threshold in [0, 4] {
+            create.threshold = Some(threshold);
+            let msg = ExecuteMsg::CreateEscrow(create.clone());
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidThreshold {}));
+        }
+
+        // 3-of-3 requires every approver
+        create.threshold = Some(3);
+        let msg = ExecuteMsg::CreateEscrow(create.clone());
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.threshold, 3);
+
+        for approver in ["approver1", "approver2"] {
+            let info = mock_info(approver, &[]);
+            let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+            let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            assert!(res.messages.is_empty());
+        }
+        let info = mock_info("approver3", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // Without a threshold the default 2-of-3 applies
+        create.threshold = None;
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(create);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 2 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.threshold, 2);
+    }
+
+    #[test]
+    fn approver_list() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let mut create = CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        };
+
+        // At least one and at most ten approvers
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::CreateEscrow(create.clone())).unwrap_err();
+        assert!(matches!(err, ContractError::NoApprovers {}));
+
+        create.approvers = (0..11).map(|i| format!("approver{}", i)).collect();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::CreateEscrow(create.clone())).unwrap_err();
+        assert!(matches!(err, ContractError::TooManyApprovers {}));
+
+        // Five unique approvers (one listed twice) default to a 3-of-5 majority
+        create.approvers = vec![
+            "approver1".to_string(),
+            "approver2".to_string(),
+            "approver3".to_string(),
+            "approver2".to_string(),
+            "approver4".to_string(),
+            "approver5".to_string(),
+        ];
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::CreateEscrow(create.clone())).unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateApprover {}));
+
+        create.allow_duplicate_approvers = Some(true);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CreateEscrow(create)).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.approvers.len(), 5);
+        assert_eq!(escrow.threshold, 3);
+
+        for approver in ["approver5", "approver2"] {
+            let info = mock_info(approver, &[]);
+            let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+            let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            assert!(res.messages.is_empty());
+        }
+        let info = mock_info("approver1", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // Every approver is indexed exactly once
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetEscrowsByAddress {
+                address: "approver2".to_string(),
+                status: None,
+                role: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let list: EscrowListResponse = from_json(&res).unwrap();
+        assert_eq!(list.escrows.len(), 1);
+    }
+
+    #[test]
+    fn approve_cancel_after_partial_approval() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // approver1 approves the release, so the creator can no longer cancel alone
+        let info = mock_info("approver1", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // Outsiders and the creator cannot vote to cancel
+        let info = mock_info("outsider", &[]);
+        let msg = ExecuteMsg::ApproveCancel { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotAnApprover {}));
+
+        // approver2 votes to cancel; a single vote is not enough
+        let info = mock_info("approver2", &[]);
+        let msg = ExecuteMsg::ApproveCancel { escrow_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        assert!(res.messages.is_empty());
+
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyApproved {}));
+
+        // Cancel votes and release approvals are tracked separately
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.approvals.len(), 1);
+        assert_eq!(escrow.approvals[0].approver, Addr::unchecked("approver1"));
+        assert_eq!(escrow.cancel_votes, vec![Addr::unchecked("approver2")]);
+
+        // approver3 tips the cancel vote over the 2-of-3 threshold
+        let info = mock_info("approver3", &[]);
+        let msg = ExecuteMsg::ApproveCancel { escrow_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+
+        // The release can no longer happen
+        let info = mock_info("approver2", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowCompleted {}));
+    }
+
+    #[test]
+    fn query_escrows_by_status() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Create four escrows
+        for _ in 0..4 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // Release escrow 1 and cancel escrow 2, leaving 3 and 4 open
+        let info = mock_info("approver1", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let all_ids = |deps: Deps, status: Option<EscrowStatus>, limit: Option<u32>| -> Vec<u64> {
+            let msg =
+                QueryMsg::GetAllEscrows { status, include_completed: None, start_after: None, limit, order: None };
+            let res: EscrowListResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+        assert_eq!(all_ids(deps.as_ref(), None, None), vec![1, 2, 3, 4]);
+        assert_eq!(all_ids(deps.as_ref(), Some(EscrowStatus::Released), None), vec![1]);
+        assert_eq!(all_ids(deps.as_ref(), Some(EscrowStatus::Cancelled), None), vec![2]);
+        assert_eq!(all_ids(deps.as_ref(), Some(EscrowStatus::Open), None), vec![3, 4]);
+
+        // The limit only counts matching escrows
+        assert_eq!(all_ids(deps.as_ref(), Some(EscrowStatus::Open), Some(1)), vec![3]);
+
+        let address_ids = |deps: Deps, status: Option<EscrowStatus>| -> Vec<u64> {
+            let msg = QueryMsg::GetEscrowsByAddress {
+                address: "beneficiary".to_string(),
+                status,
+                role: None,
+                start_after: None,
+                limit: Some(1),
+            };
+            let res: EscrowListResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+        assert_eq!(address_ids(deps.as_ref(), Some(EscrowStatus::Released)), vec![1]);
+        assert_eq!(address_ids(deps.as_ref(), Some(EscrowStatus::Open)), vec![3]);
+        // Cancelled escrows stay in the address indexes like released ones
+        assert_eq!(address_ids(deps.as_ref(), Some(EscrowStatus::Cancelled)), vec![2]);
+    }
+
+    #[test]
+    fn migrate_v1_escrows() {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { .. } => SystemResult::Ok(ContractResult::Ok(Binary::from(
+                br#"{"code_id":1,"creator":"deployer","admin":"admin","pinned":false}"#.as_slice(),
+            ))),
+            _ => SystemResult::Err(SystemError::Unknown {}),
+        });
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cosmoscrow", "0.1.0").unwrap();
+
+        let old = EscrowV1 {
+            id: 1,
+            creator: Addr::unchecked("creator"),
+            beneficiary: Addr::unchecked("beneficiary"),
+            amount: Coin::new(1000, "ujuno"),
+            approver1: Addr::unchecked("approver1"),
+            approver2: Addr::unchecked("approver2"),
+            approver3: Some(Addr::unchecked("approver1")),
+            description: "Test escrow".to_string(),
+            approvals: vec![],
+            is_completed: false,
+            created_at: 100,
+            completed_at: None,
+        };
+        let released = EscrowV1 {
+            id: 2,
+            approvals: vec![Addr::unchecked("approver1"), Addr::unchecked("approver2")],
+            is_completed: true,
+            completed_at: Some(200),
+            ..old.clone()
+        };
+        let cancelled = EscrowV1 {
+            id: 3,
+            is_completed: true,
+            ..old.clone()
+        };
+        for escrow in [&old, &released, &cancelled] {
+            ESCROWS_V1.save(deps.as_mut().storage, escrow.id, escrow).unwrap();
+        }
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let escrow = ESCROWS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Open);
+        assert_eq!(escrow.amount, coins(1000, "ujuno"));
+        assert_eq!(escrow.approvers, vec![Addr::unchecked("approver1"), Addr::unchecked("approver2")]);
+
+        let escrow = ESCROWS.load(deps.as_ref().storage, 2).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Released);
+        assert_eq!(escrow.completed_at, Some(200));
+        assert_eq!(
+            escrow.approvals,
+            vec![
+                Approval { approver: Addr::unchecked("approver1"), approved_at: 0, comment: None },
+                Approval { approver: Addr::unchecked("approver2"), approved_at: 0, comment: None },
+            ]
+        );
+
+        let escrow = ESCROWS.load(deps.as_ref().storage, 3).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Cancelled);
+
+        // Address indexes are rebuilt, refunded escrow included
+        let msg = QueryMsg::GetEscrowsByAddress {
+            address: "approver1".to_string(),
+            status: None,
+            role: None,
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let list: EscrowListResponse = from_json(&res).unwrap();
+        assert_eq!(list.escrows.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        // Stats are computed from the migrated escrows
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetStats {}).unwrap();
+        let stats: StatsResponse = from_json(&res).unwrap();
+        assert_eq!(stats.total_escrows, 3);
+        assert_eq!(stats.open_count, 1);
+        assert_eq!(stats.completed_count, 2);
+        assert_eq!(stats.total_locked, coins(1000, "ujuno"));
+
+        // Migrated contracts start without a platform fee
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let config: ConfigResponse = from_json(&res).unwrap();
+        assert_eq!(config.fee_bps, 0);
+        assert_eq!(config.admin, Addr::unchecked("admin"));
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn platform_fee_on_release() {
+        let mut deps = mock_funded_dependencies();
+
+        // A fee without a recipient or above 100% is rejected
+        let msg = InstantiateMsg { fee_recipient: None, fee_bps: Some(100), ..Default::default() };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFee {}));
+        let msg = InstantiateMsg { fee_recipient: Some("treasury".to_string()), fee_bps: Some(10_001), ..Default::default() };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFee {}));
+
+        // 1% fee
+        let msg = InstantiateMsg { fee_recipient: Some("treasury".to_string()), fee_bps: Some(100), ..Default::default() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let config: ConfigResponse = from_json(&res).unwrap();
+        assert_eq!(config.fee_recipient, Some(Addr::unchecked("treasury")));
+        assert_eq!(config.fee_bps, 100);
+
+        // 1% of 199 rounds down to 1, of 99 rounds down to 0
+        let info = mock_info("creator", &[Coin::new(199, "ujuno"), Coin::new(99, "uusdc")]);
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("approver1", &[]);
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: vec![Coin::new(198, "ujuno"), Coin::new(99, "uusdc")],
+            })
+        );
+
+        // The fee stays in the contract for the fee recipient
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAccruedFees {}).unwrap();
+        assert_eq!(from_json::<AccruedFeesResponse>(&res).unwrap().fees, coins(1, "ujuno"));
+    }
+
+    #[test]
+    fn pause_blocks_creation_only() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        execute(deps.as_mut(), mock_env(), info.clone(), create.clone()).unwrap();
+
+        // Only the admin can pause
+        let msg = ExecuteMsg::SetPaused { paused: true };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let config: ConfigResponse = from_json(&res).unwrap();
+        assert!(config.paused);
+
+        // Creation fails while paused
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), create.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+
+        // ...but existing escrows can still be released
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // Unpausing allows creation again
+        let msg = ExecuteMsg::SetPaused { paused: false };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), info, create).unwrap();
+    }
+
+    #[test]
+    fn transfer_creator() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            threshold: Some(2),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Only the creator can transfer
+        let msg = ExecuteMsg::TransferCreator { escrow_id: 1, new_creator: "new_creator".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // Transferring to an approver would make the explicit 2-of-2 threshold unreachable
+        let bad = ExecuteMsg::TransferCreator { escrow_id: 1, new_creator: "approver1".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), bad).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidThreshold {}));
+
+        // Nobody could act as creator on the contract's behalf
+        let contract = mock_env().contract.address.to_string();
+        let bad = ExecuteMsg::TransferCreator { escrow_id: 1, new_creator: contract };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), bad).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidCreator {}));
+
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // The creator index moved to the new creator
+        let by_address = |deps: Deps, address: &str| -> Vec<u64> {
+            let msg = QueryMsg::GetEscrowsByAddress {
+                address: address.to_string(),
+                status: None,
+                role: None,
+                start_after: None,
+                limit: None,
+            };
+            let res: EscrowListResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+        assert!(by_address(deps.as_ref(), "creator").is_empty());
+        assert_eq!(by_address(deps.as_ref(), "new_creator"), vec![1]);
+
+        // The old creator can no longer cancel, the new one gets the refund
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("new_creator", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_creator".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+    }
+
+    #[test]
+    fn dispute_resolved_by_arbiter() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        for arbiter in [None, Some("arbiter".to_string()), Some("arbiter".to_string())] {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string(), "approver2".to_string()],
+                description: "Test escrow".to_string(),
+                arbiter,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // Escrow 1 has no arbiter to settle a dispute
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NoArbiter {}));
+
+        // Only the creator or beneficiary can dispute
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 2 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // The beneficiary disputes escrow 2, the creator disputes escrow 3
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("beneficiary", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 3 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // Approvals are blocked while disputed
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Disputed {}));
+
+        // Only the arbiter can resolve
+        let msg = ExecuteMsg::ResolveDispute { escrow_id: 2, award_to_beneficiary: true };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // Award escrow 2 to the beneficiary
+        let res = execute(deps.as_mut(), mock_env(), mock_info("arbiter", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 2 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Released);
+
+        // Refund escrow 3 to the creator
+        let msg = ExecuteMsg::ResolveDispute { escrow_id: 3, award_to_beneficiary: false };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("arbiter", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 3 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Cancelled);
+    }
+
+    #[test]
+    fn release_requires_acceptance() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg {
+            require_acceptance: Some(true),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        // The final approval cannot release before the beneficiary accepts
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotAccepted {}));
+
+        // Only the beneficiary can accept
+        let msg = ExecuteMsg::AcceptEscrow { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let msg = ExecuteMsg::AcceptEscrow { escrow_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("beneficiary", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert!(escrow.accepted);
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Released);
+    }
+
+    #[test]
+    fn query_escrows_by_address_pagination() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // alice is creator of 1, beneficiary of 2, approver of 3 and both creator and approver of 4
+        let escrows = [
+            ("alice", "bob", vec!["carol"]),
+            ("bob", "alice", vec!["carol"]),
+            ("bob", "carol", vec!["alice"]),
+            ("alice", "bob", vec!["alice", "carol"]),
+            ("bob", "carol", vec!["dave"]),
+        ];
+        for (creator, beneficiary, approvers) in escrows {
+            let info = mock_info(creator, &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![(beneficiary.to_string(), 1)],
+                approvers: approvers.into_iter().map(String::from).collect(),
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let page = |deps: Deps, start_after: Option<u64>| -> Vec<u64> {
+            let msg = QueryMsg::GetEscrowsByAddress {
+                address: "alice".to_string(),
+                status: None,
+                role: None,
+                start_after,
+                limit: Some(2),
+            };
+            let res = query(deps, mock_env(), msg).unwrap();
+            let list: EscrowListResponse = from_json(&res).unwrap();
+            list.escrows.into_iter().map(|e| e.id).collect()
+        };
+
+        assert_eq!(page(deps.as_ref(), None), vec![1, 2]);
+        assert_eq!(page(deps.as_ref(), Some(2)), vec![3, 4]);
+        assert_eq!(page(deps.as_ref(), Some(4)), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn stats_track_escrow_lifecycle() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let stats = |deps: Deps| -> StatsResponse {
+            from_json(query(deps, mock_env(), QueryMsg::GetStats {}).unwrap()).unwrap()
+        };
+        assert_eq!(stats(deps.as_ref()).total_escrows, 0);
+        assert!(stats(deps.as_ref()).total_locked.is_empty());
+
+        for funds in [vec![Coin::new(1000, "ujuno"), Coin::new(50, "uusdc")], coins(500, "ujuno")] {
+            let info = mock_info("creator", &funds);
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let res = stats(deps.as_ref());
+        assert_eq!(res.total_escrows, 2);
+        assert_eq!(res.open_count, 2);
+        assert_eq!(res.completed_count, 0);
+        assert_eq!(res.total_locked, vec![Coin::new(1500, "ujuno"), Coin::new(50, "uusdc")]);
+
+        // Releasing escrow 1 unlocks its funds
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        let res = stats(deps.as_ref());
+        assert_eq!(res.total_escrows, 2);
+        assert_eq!(res.open_count, 1);
+        assert_eq!(res.completed_count, 1);
+        assert_eq!(res.total_locked, coins(500, "ujuno"));
+
+        // Cancelling escrow 2 refunds the rest
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = stats(deps.as_ref());
+        assert_eq!(res.open_count, 0);
+        assert_eq!(res.completed_count, 2);
+        assert!(res.total_locked.is_empty());
+    }
+
+    #[test]
+    fn indexer_events() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let event_types = |res: &Response| -> Vec<String> {
+            res.events.iter().map(|event| event.ty.clone()).collect()
+        };
+
+        let mut created = vec![];
+        for _ in 0..2 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            created.push(execute(deps.as_mut(), mock_env(), info, msg).unwrap());
+        }
+        assert_eq!(event_types(&created[0]), vec!["escrow_created"]);
+        let event = &created[0].events[0];
+        assert!(event.attributes.contains(&Attribute::new("escrow_id", "1")));
+        assert!(event.attributes.contains(&Attribute::new("amount", "1000ujuno")));
+        assert!(event.attributes.contains(&Attribute::new("denom", "ujuno")));
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert_eq!(event_types(&res), vec!["escrow_approved", "escrow_released"]);
+        assert!(res.events[0].attributes.contains(&Attribute::new("approval_count", "1")));
+
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 2 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(event_types(&res), vec!["escrow_cancelled"]);
+        assert!(res.events[0].attributes.contains(&Attribute::new("reason", "cancelled")));
+    }
+
+    #[test]
+    fn description_length_limit() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            max_description_length: Some(10),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = |description: &str| ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: description.to_string(),
+            ..Default::default()
+        });
+
+        // Ten characters are allowed even though they take more than ten bytes
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        execute(deps.as_mut(), mock_env(), info, create("éééééééééé")).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let err = execute(deps.as_mut(), mock_env(), info, create("ééééééééééé")).unwrap_err();
+        assert!(matches!(err, ContractError::DescriptionTooLong {}));
+
+        // The default limit is 500 characters
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        execute(deps.as_mut(), mock_env(), info, create(&"a".repeat(500))).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let err = execute(deps.as_mut(), mock_env(), info, create(&"a".repeat(501))).unwrap_err();
+        assert!(matches!(err, ContractError::DescriptionTooLong {}));
+    }
+
+    #[test]
+    fn beneficiary_can_be_approver() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["beneficiary".to_string(), "approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.approvers, vec![Addr::unchecked("beneficiary"), Addr::unchecked("approver1")]);
+    }
+
+    #[test]
+    fn approvals_record_block_time() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        for approver in ["approver2", "approver1"] {
+            env.block.time = env.block.time.plus_seconds(60);
+            let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+            execute(deps.as_mut(), env.clone(), mock_info(approver, &[]), msg).unwrap();
+        }
+
+        let start = mock_env().block.time.seconds();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(
+            escrow.approvals,
+            vec![
+                Approval { approver: Addr::unchecked("approver2"), approved_at: start + 60, comment: None },
+                Approval { approver: Addr::unchecked("approver1"), approved_at: start + 120, comment: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn migrate_v2_approvals() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cosmoscrow", "0.3.0").unwrap();
+
+        let old = EscrowV2 {
+            id: 1,
+            creator: Addr::unchecked("creator"),
+            beneficiary: Addr::unchecked("beneficiary"),
+            amount: coins(1000, "ujuno"),
+            approvers: vec![Addr::unchecked("approver1"), Addr::unchecked("approver2")],
+            description: "Test escrow".to_string(),
+            approvals: vec![Addr::unchecked("approver1")],
+            cancel_votes: vec![],
+            threshold: None,
+            status: EscrowStatus::Open,
+            created_at: 100,
+            completed_at: None,
+            expires_at: Some(500),
+            arbiter: None,
+            disputed: false,
+            accepted: true,
+        };
+        ESCROWS_V2.save(deps.as_mut().storage, 1, &old).unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let escrow = ESCROWS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(escrow.approvals, vec![Approval { approver: Addr::unchecked("approver1"), approved_at: 0, comment: None }]);
+        assert_eq!(escrow.expires_at, Some(500));
+        assert_eq!(escrow.beneficiaries, vec![(Addr::unchecked("beneficiary"), 1)]);
+        assert!(escrow.is_accepted());
+    }
+
+    #[test]
+    fn revoke_approval() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Nothing to revoke yet
+        let msg = ExecuteMsg::RevokeApproval { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotApproved {}));
+
+        // One short of the 2-of-3 threshold, approver1 changes their mind
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RevokeApproval { escrow_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert_eq!(res.attributes[3].value, "0"); // total_approvals
+
+        // approver2 alone does not release
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+
+        // approver1 approves again and the threshold is reached
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // Approvals can no longer be revoked after the release
+        let msg = ExecuteMsg::RevokeApproval { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowCompleted {}));
+    }
+
+    #[test]
+    fn time_locked_release() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let release_after = mock_env().block.time.seconds() + 1000;
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            release_after: Some(release_after),
+            expires_at: Some(release_after),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidExpiration {}));
+
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            release_after: Some(release_after),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Nothing to release before the approval
+        let msg = ExecuteMsg::Release { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::ConditionsNotMet {}));
+
+        // The approval is recorded but the funds are held
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Open);
+        assert_eq!(escrow.approvals.len(), 1);
+
+        // One second before the time lock ends
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(999);
+        let msg = ExecuteMsg::Release { escrow_id: 1 };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::TooEarly {}));
+
+        // Anyone can release once it ends
+        env.block.time = env.block.time.plus_seconds(1);
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+    }
+
+    #[test]
+    fn split_release_between_beneficiaries() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = |beneficiaries: Vec<(&str, u64)>| ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: beneficiaries.into_iter().map(|(addr, weight)| (addr.to_string(), weight)).collect(),
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+
+        // Weights must be non-zero and addresses unique
+        let info = mock_info("creator", &[Coin::new(1000, "ujuno"), Coin::new(2, "uusdc")]);
+        for beneficiaries in [vec![], vec![("alice", 1), ("bob", 0)], vec![("alice", 1), ("alice", 2)]] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), create(beneficiaries)).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidBeneficiary {}));
+        }
+
+        // 1000 split 1:1:1 leaves 1 over, 2 leaves 2 over, both for alice
+        execute(deps.as_mut(), mock_env(), info, create(vec![("alice", 1), ("bob", 1), ("carol", 1)])).unwrap();
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        let sends: Vec<CosmosMsg> = res.messages.into_iter().map(|sub| sub.msg).collect();
+        assert_eq!(
+            sends,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "alice".to_string(),
+                    amount: vec![Coin::new(334, "ujuno"), Coin::new(2, "uusdc")],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "bob".to_string(),
+                    amount: coins(333, "ujuno"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "carol".to_string(),
+                    amount: coins(333, "ujuno"),
+                }),
+            ]
+        );
+
+        // Every beneficiary is indexed
+        for address in ["alice", "bob", "carol"] {
+            let msg = QueryMsg::GetEscrowsByAddress {
+                address: address.to_string(),
+                status: None,
+                role: None,
+                start_after: None,
+                limit: None,
+            };
+            let list: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(list.escrows.len(), 1);
+        }
+    }
+
+    #[test]
+    fn query_escrows_by_time_range() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // Escrows 1 to 5 created at 100, 200, 200, 300 and 400 seconds after the start
+        let start = mock_env().block.time.seconds();
+        for offset in [100, 200, 200, 300, 400] {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(offset);
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env, info, msg).unwrap();
+        }
+
+        let ids = |deps: Deps, start_time: u64, end_time: u64, start_after: Option<(u64, u64)>| -> Vec<u64> {
+            let msg = QueryMsg::GetEscrowsByTimeRange { start_time, end_time, start_after, limit: Some(2) };
+            let list: EscrowListResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            list.escrows.into_iter().map(|e| e.id).collect()
+        };
+
+        // Both ends of the range are inclusive
+        assert_eq!(ids(deps.as_ref(), start + 200, start + 300, None), vec![2, 3]);
+        assert_eq!(ids(deps.as_ref(), start + 200, start + 300, Some((start + 200, 3))), vec![4]);
+        assert_eq!(ids(deps.as_ref(), start + 150, start + 250, None), vec![2, 3]);
+        assert_eq!(ids(deps.as_ref(), start + 200, start + 200, Some((start + 200, 2))), vec![3]);
+
+        // A cursor before the range starts at the range, empty ranges return nothing
+        assert_eq!(ids(deps.as_ref(), start + 300, start + 500, Some((start, 1))), vec![4, 5]);
+        assert_eq!(ids(deps.as_ref(), start + 301, start + 399, None), Vec::<u64>::new());
+        assert_eq!(ids(deps.as_ref(), start + 400, start + 100, None), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn release_checks_contract_balance() {
+        // The contract holds less than the escrowed amount
+        let mut deps = mock_dependencies_with_balance(&coins(999, "ujuno"));
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientContractBalance {}));
+
+        // The escrow is left open
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Open);
+        assert!(escrow.completed_at.is_none());
+    }
+
+    #[test]
+    fn update_description() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            max_description_length: Some(20),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let update = |description: &str| ExecuteMsg::UpdateDescription {
+            escrow_id: 1,
+            description: description.to_string(),
+        };
+
+        // Only the creator can update, within the length limit
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), update("New terms")).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update(&"a".repeat(21))).unwrap_err();
+        assert!(matches!(err, ContractError::DescriptionTooLong {}));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update("New terms")).unwrap();
+        assert_eq!(res.attributes[2].value, "Test escrow"); // old_description
+        assert_eq!(res.attributes[3].value, "New terms"); // new_description
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.description, "New terms");
+
+        // No more changes after the first approval
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update("Other terms")).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn top_up() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &[Coin::new(1000, "ujuno"), Coin::new(10, "uusdc")]);
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Only the creator can top up, and only with denoms the escrow holds
+        let msg = ExecuteMsg::TopUp { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &coins(500, "ujuno")), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(500, "uatom")), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::DenomMismatch {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(500, "ujuno")), msg.clone()).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetStats {}).unwrap();
+        let stats: StatsResponse = from_json(&res).unwrap();
+        assert_eq!(stats.total_locked, vec![Coin::new(1500, "ujuno"), Coin::new(10, "uusdc")]);
+
+        // No top ups once approved
+        let approve = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(500, "ujuno")), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // The release pays out the topped up amount
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), approve).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: vec![Coin::new(1500, "ujuno"), Coin::new(10, "uusdc")],
+            })
+        );
+    }
+
+    #[test]
+    fn approval_modes() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = |threshold: Option<u32>, mode: ApprovalMode| ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+            description: "Test escrow".to_string(),
+            threshold,
+            mode: Some(mode),
+            ..Default::default()
+        });
+
+        // A mode cannot be combined with an explicit threshold
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), create(Some(1), ApprovalMode::Single)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidThreshold {}));
+
+        // Escrows 1, 2 and 3 need 3, 2 and 1 approvals
+        for mode in [ApprovalMode::Unanimous, ApprovalMode::Majority, ApprovalMode::Single] {
+            execute(deps.as_mut(), mock_env(), info.clone(), create(None, mode)).unwrap();
+        }
+
+        for (escrow_id, threshold) in [(1, 3), (2, 2), (3, 1)] {
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id }).unwrap();
+            let escrow: EscrowResponse = from_json(&res).unwrap();
+            assert_eq!(escrow.threshold, threshold);
+
+            // Only the approval reaching the threshold releases the funds
+            for (i, approver) in ["approver1", "approver2", "approver3"].into_iter().enumerate().take(threshold as usize) {
+                let msg = ExecuteMsg::ApproveRelease { escrow_id, comment: None };
+                let res = execute(deps.as_mut(), mock_env(), mock_info(approver, &[]), msg).unwrap();
+                assert_eq!(res.messages.len(), usize::from(i + 1 == threshold as usize));
+            }
+        }
+    }
+
+    #[test]
+    fn escrow_counter_overflow() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+
+        // The last ID is still handed out
+        ESCROW_COUNTER.save(deps.as_mut().storage, &(u64::MAX - 1)).unwrap();
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, u64::MAX.to_string()); // escrow_id
+
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::CounterOverflow {}));
+    }
+
+    #[test]
+    fn idempotent_creation() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            idempotency_key: Some("order-42".to_string()),
+            ..Default::default()
+        });
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, "1"); // escrow_id
+
+        // The retry returns the same escrow and refunds the duplicate funds
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, "1"); // escrow_id
+        assert_eq!(res.attributes[2].value, "true"); // duplicate
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+
+        // Keys are scoped per creator
+        let info = mock_info("other", &coins(1000, "ujuno"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes[1].value, "2"); // escrow_id
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetStats {}).unwrap();
+        let stats: StatsResponse = from_json(&res).unwrap();
+        assert_eq!(stats.total_escrows, 2);
+    }
+
+    #[test]
+    fn query_role() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["creator".to_string(), "approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        let role = |deps: Deps, address: &str| -> RoleResponse {
+            let msg = QueryMsg::GetRole { escrow_id: 1, address: address.to_string() };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // The creator is listed as an approver but cannot approve
+        let res = role(deps.as_ref(), "creator");
+        assert!(res.is_creator && res.is_approver && !res.can_approve);
+
+        let res = role(deps.as_ref(), "beneficiary");
+        assert!(res.is_beneficiary && !res.is_approver && !res.can_approve);
+
+        let res = role(deps.as_ref(), "approver1");
+        assert!(res.is_approver && res.has_approved && !res.can_approve);
+
+        let res = role(deps.as_ref(), "approver2");
+        assert!(res.is_approver && !res.has_approved && res.can_approve);
+
+        let res = role(deps.as_ref(), "stranger");
+        assert_eq!(
+            res,
+            RoleResponse {
+                is_creator: false,
+                is_beneficiary: false,
+                is_approver: false,
+                has_approved: false,
+                can_approve: false,
+            }
+        );
+    }
+
+    #[test]
+    fn approve_release_batch() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // Escrow 1 releases on approver1's approval, escrow 2 needs both approvers and
+        // escrow 3 does not list approver1
+        for approvers in [vec!["approver1"], vec!["approver1", "approver2"], vec!["approver2"]] {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: approvers.into_iter().map(String::from).collect(),
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let msg = ExecuteMsg::ApproveReleaseBatch { escrow_ids: (0..31).collect() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::BatchTooLarge {}));
+
+        // Escrow 2 is already approved, escrow 3 and 4 can't be approved
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::ApproveReleaseBatch { escrow_ids: vec![1, 2, 3, 4] };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.attributes[2].value, "1"); // approved
+        assert_eq!(res.attributes[3].value, "2,3,4"); // skipped
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Released);
+    }
+
+    #[test]
+    fn minimum_escrow_amount() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            min_amounts: Some(coins(100, "ujuno")),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let config: ConfigResponse = from_json(&res).unwrap();
+        assert_eq!(config.min_amounts, coins(100, "ujuno"));
+
+        let create = |funds: &[Coin]| {
+            let info = mock_info("creator", funds);
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            (info, msg)
+        };
+
+        let (info, msg) = create(&coins(99, "ujuno"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::BelowMinimum {}));
+
+        // Every listed denom in the escrow must meet its minimum
+        let (info, msg) = create(&[Coin::new(99, "ujuno"), Coin::new(1000, "uusdc")]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::BelowMinimum {}));
+
+        let (info, msg) = create(&coins(100, "ujuno"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let (info, msg) = create(&coins(101, "ujuno"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Denoms without a minimum are not restricted
+        let (info, msg) = create(&coins(1, "uusdc"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn failed_release_send_reopens_escrow() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].id, 1);
+        assert_eq!(res.messages[0].reply_on, ReplyOn::Error);
+
+        // Simulate the send failing
+        let msg = Reply {
+            id: 1,
+            result: SubMsgResult::Err("insufficient funds".to_string()),
+        };
+        let res = reply(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.attributes[0].value, "release_failed");
+        assert_eq!(res.attributes[2].value, "insufficient funds");
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Open);
+        assert!(escrow.completed_at.is_none());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetStats {}).unwrap();
+        let stats: StatsResponse = from_json(&res).unwrap();
+        assert_eq!(stats.open_count, 1);
+        assert_eq!(stats.completed_count, 0);
+        assert_eq!(stats.total_locked, coins(1000, "ujuno"));
+        let msg = QueryMsg::CountEscrows { status: Some(EscrowStatus::Released) };
+        assert_eq!(from_json::<u64>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap(), 0);
+
+        // The approvals are kept, so the release can be retried
+        let msg = ExecuteMsg::Release { escrow_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn settle_cancellation() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &[Coin::new(1000, "ujuno"), Coin::new(50, "uusdc")]);
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary1".to_string(), 3), ("beneficiary2".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let settle = |to_beneficiary: Vec<Coin>| ExecuteMsg::SettleCancellation {
+            escrow_id: 1,
+            to_beneficiary,
+        };
+
+        // The split can't exceed the escrowed funds or use other denoms
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), settle(coins(1001, "ujuno")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSettlement {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), settle(coins(1, "uatom")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSettlement {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), settle(coins(400, "ujuno")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // A different split restarts the agreement
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), settle(coins(400, "ujuno"))).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("beneficiary1", &[]), settle(coins(600, "ujuno"))).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        let settlement = escrow.settlement.unwrap();
+        assert_eq!(settlement.to_beneficiary, coins(600, "ujuno"));
+        assert_eq!(settlement.agreed_by, vec![Addr::unchecked("beneficiary1")]);
+
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), settle(coins(600, "ujuno"))).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("beneficiary2", &[]), settle(coins(600, "ujuno")))
+            .unwrap();
+        assert_eq!(res.attributes[3].value, "true"); // settled
+
+        // 600ujuno split 3:1 between the beneficiaries, the rest back to the creator
+        assert_eq!(res.messages.len(), 3);
+        let sends: Vec<(String, Vec<Coin>)> = res
+            .messages
+            .iter()
+            .map(|msg| match &msg.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => (to_address.clone(), amount.clone()),
+                _ => panic!("Expected bank send"),
+            })
+            .collect();
+        assert_eq!(sends[0], ("beneficiary1".to_string(), coins(450, "ujuno")));
+        assert_eq!(sends[1], ("beneficiary2".to_string(), coins(150, "ujuno")));
+        assert_eq!(
+            sends[2],
+            ("creator".to_string(), vec![Coin::new(400, "ujuno"), Coin::new(50, "uusdc")])
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Cancelled);
+    }
+
+    #[test]
+    fn arbiter_settles_cancellation() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            arbiter: Some("arbiter".to_string()),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Once disputed only the arbiter can settle, and it does so on its own
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("beneficiary", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::SettleCancellation { escrow_id: 1, to_beneficiary: coins(1000, "ujuno") };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("beneficiary", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Disputed {}));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("arbiter", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+    }
+
+    #[test]
+    fn reclaim_unaccepted() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let deadline = mock_env().block.time.seconds() + 100;
+        for _ in 0..2 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                accept_deadline: Some(deadline),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // Escrow 2 gets accepted in time
+        let msg = ExecuteMsg::AcceptEscrow { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("beneficiary", &[]), msg).unwrap();
+
+        let reclaim = |escrow_id| ExecuteMsg::ReclaimUnaccepted { escrow_id };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), reclaim(1)).unwrap_err();
+        assert!(matches!(err, ContractError::TooEarly {}));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(101);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("beneficiary", &[]), reclaim(1)).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), reclaim(2)).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyAccepted {}));
+
+        let res = execute(deps.as_mut(), env, mock_info("creator", &[]), reclaim(1)).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Cancelled);
+    }
+
+    #[test]
+    fn approval_status() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec![
+                "creator".to_string(),
+                "approver1".to_string(),
+                "approver2".to_string(),
+                "approver3".to_string(),
+            ],
+            description: "Test escrow".to_string(),
+            threshold: Some(2),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetApprovalStatus { escrow_id: 1 }).unwrap();
+        let status: ApprovalStatusResponse = from_json(&res).unwrap();
+        assert_eq!(status.required, 2);
+        assert_eq!(status.current, 1);
+        assert_eq!(status.remaining, 1);
+        assert_eq!(status.approved, vec![Addr::unchecked("approver2")]);
+        // The creator can't approve and is not pending
+        assert_eq!(status.pending, vec![Addr::unchecked("approver1"), Addr::unchecked("approver3")]);
+    }
+
+    #[test]
+    fn ownership_transfer() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let transfer = ExecuteMsg::UpdateOwnership(OwnershipAction::TransferOwnership {
+            new_owner: "new_owner".to_string(),
+            expiry: None,
+        });
+        let accept = ExecuteMsg::UpdateOwnership(OwnershipAction::AcceptOwnership {});
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), transfer.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("new_owner", &[]), accept.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingOwner {}));
+
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), transfer).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Ownership {}).unwrap();
+        let ownership: OwnershipResponse = from_json(&res).unwrap();
+        assert_eq!(ownership.owner, Addr::unchecked("admin"));
+        assert_eq!(ownership.pending_owner, Some(Addr::unchecked("new_owner")));
+
+        // Only the proposed owner can accept
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), accept.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("new_owner", &[]), accept).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Ownership {}).unwrap();
+        let ownership: OwnershipResponse = from_json(&res).unwrap();
+        assert_eq!(ownership.owner, Addr::unchecked("new_owner"));
+        assert_eq!(ownership.pending_owner, None);
+
+        // Admin actions move to the new owner
+        let msg = ExecuteMsg::SetPaused { paused: true };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("new_owner", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn expired_ownership_transfer() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateOwnership(OwnershipAction::TransferOwnership {
+            new_owner: "new_owner".to_string(),
+            expiry: Some(mock_env().block.time.seconds() + 100),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(101);
+        let msg = ExecuteMsg::UpdateOwnership(OwnershipAction::AcceptOwnership {});
+        let err = execute(deps.as_mut(), env, mock_info("new_owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::OwnershipTransferExpired {}));
+
+        let msg = ExecuteMsg::UpdateOwnership(OwnershipAction::CancelTransfer {});
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Ownership {}).unwrap();
+        let ownership: OwnershipResponse = from_json(&res).unwrap();
+        assert_eq!(ownership.pending_owner, None);
+    }
+
+    #[test]
+    fn allowed_denoms() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            allowed_denoms: Some(vec!["ujuno".to_string(), "uusdc".to_string()]),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = |funds: &[Coin]| {
+            let info = mock_info("creator", funds);
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            (info, msg)
+        };
+
+        let (info, msg) = create(&[Coin::new(1000, "ujuno"), Coin::new(1000, "uusdc")]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let (info, msg) = create(&[Coin::new(1000, "ujuno"), Coin::new(1000, "uscam")]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::DenomNotAllowed {}));
+
+        // Only the admin can change the whitelist
+        let msg = ExecuteMsg::UpdateAllowedDenoms { allowed_denoms: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let config: ConfigResponse = from_json(&res).unwrap();
+        assert_eq!(config.allowed_denoms, None);
+
+        // Without a whitelist every denom is accepted
+        let (info, msg) = create(&coins(1000, "uscam"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn all_escrows_ordering() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        for _ in 0..5 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let page = |start_after: Option<u64>, order: Option<SortOrder>| -> Vec<u64> {
+            let msg =
+                QueryMsg::GetAllEscrows { status: None, include_completed: None, start_after, limit: Some(2), order };
+            let res: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+
+        assert_eq!(page(None, None), vec![1, 2]);
+        assert_eq!(page(Some(2), Some(SortOrder::Ascending)), vec![3, 4]);
+
+        // Most recent first, with `start_after` as an exclusive upper bound
+        assert_eq!(page(None, Some(SortOrder::Descending)), vec![5, 4]);
+        assert_eq!(page(Some(4), Some(SortOrder::Descending)), vec![3, 2]);
+        assert_eq!(page(Some(2), Some(SortOrder::Descending)), vec![1]);
+        assert_eq!(page(Some(1), Some(SortOrder::Descending)), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn approval_delegate() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Only approvers can appoint a delegate
+        let msg = ExecuteMsg::SetApprovalDelegate { escrow_id: 1, delegate: "bot".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NotAnApprover {}));
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        let approve = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other_bot", &[]), approve.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NotAnApprover {}));
+
+        // The delegate's approval counts as approver1's
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), approve.clone()).unwrap();
+        assert_eq!(res.attributes[2].value, "approver1"); // approver
+        assert_eq!(res.attributes[3].value, "1"); // total_approvals
+        assert_eq!(res.attributes[4].value, "bot"); // delegate
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyApproved {}));
+
+        // A revoked delegate can no longer approve
+        let msg = ExecuteMsg::SetApprovalDelegate { escrow_id: 1, delegate: "bot".to_string() };
+        execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RevokeApprovalDelegate { escrow_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NoDelegate {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), approve).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyApproved {}));
+    }
+
+    #[test]
+    fn block_beneficiary_approver() {
+        let create = || {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["beneficiary".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            (info, msg)
+        };
+
+        // Allowed by default
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let (info, msg) = create();
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            block_beneficiary_approver: Some(true),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let (info, msg) = create();
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::BeneficiaryCannotApprove {}));
+    }
+
+    #[test]
+    fn create_from_template() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::SaveTemplate {
+            name: "freelance".to_string(),
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            threshold: Some(1),
+            description: "Freelance job".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = QueryMsg::GetTemplates { owner: "creator".to_string(), start_after: None, limit: None };
+        let res: TemplateListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.templates.len(), 1);
+        assert_eq!(res.templates[0].name, "freelance");
+
+        // Templates belong to the address that saved them
+        let msg = ExecuteMsg::CreateFromTemplate {
+            name: "freelance".to_string(),
+            beneficiary: "beneficiary".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &coins(1000, "ujuno")), msg.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::TemplateNotFound {}));
+
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.creator, Addr::unchecked("creator"));
+        assert_eq!(escrow.beneficiaries, vec![(Addr::unchecked("beneficiary"), 1)]);
+        assert_eq!(escrow.approvers, vec![Addr::unchecked("approver1"), Addr::unchecked("approver2")]);
+        assert_eq!(escrow.threshold, 1);
+        assert_eq!(escrow.description, "Freelance job");
+        assert_eq!(escrow.amount, coins(1000, "ujuno"));
+    }
+
+    #[test]
+    fn escrow_metadata() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = |metadata: Binary| {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                metadata: Some(metadata),
+                ..Default::default()
+            });
+            (info, msg)
+        };
+
+        let metadata = Binary::from(br#"{"invoice_id":"INV-42","project":"website"}"#.as_slice());
+        let (info, msg) = create(metadata.clone());
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.metadata, Some(metadata));
+
+        let (info, msg) = create(Binary::from(vec![b'a'; 2049]));
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::MetadataTooLarge {}));
+    }
+
+    #[test]
+    fn escrows_by_address_role_filter() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // alice creates escrow 1, receives escrow 2 and approves escrow 3
+        for (creator, beneficiary, approver) in [
+            ("alice", "bob", "carol"),
+            ("bob", "alice", "carol"),
+            ("bob", "carol", "alice"),
+        ] {
+            let info = mock_info(creator, &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![(beneficiary.to_string(), 1)],
+                approvers: vec![approver.to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let ids = |role: Option<EscrowRole>| -> Vec<u64> {
+            let msg = QueryMsg::GetEscrowsByAddress {
+                address: "alice".to_string(),
+                status: None,
+                role,
+                start_after: None,
+                limit: None,
+            };
+            let res: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+
+        assert_eq!(ids(None), vec![1, 2, 3]);
+        assert_eq!(ids(Some(EscrowRole::Creator)), vec![1]);
+        assert_eq!(ids(Some(EscrowRole::Beneficiary)), vec![2]);
+        assert_eq!(ids(Some(EscrowRole::Approver)), vec![3]);
+    }
+
+    #[test]
+    fn two_phase_release() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg {
+            two_phase_release: Some(true),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Reaching the threshold only marks the escrow as ready
+        let approve = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        let get_escrow = |deps: Deps| -> EscrowResponse {
+            from_json(query(deps, mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap()).unwrap()
+        };
+        let escrow = get_escrow(deps.as_ref());
+        assert_eq!(escrow.status, EscrowStatus::Open);
+        assert!(escrow.ready_to_release);
+
+        // The approval can still be revoked
+        let msg = ExecuteMsg::RevokeApproval { escrow_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert!(!get_escrow(deps.as_ref()).ready_to_release);
+
+        let finalize = ExecuteMsg::FinalizeRelease { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), finalize.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::ConditionsNotMet {}));
+
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve).unwrap();
+
+        // Only the creator or an approver can finalize, also through `Release`
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), finalize.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let msg = ExecuteMsg::Release { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), finalize).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+        assert_eq!(get_escrow(deps.as_ref()).status, EscrowStatus::Released);
+    }
+
+    #[test]
+    fn denom_aliases() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let ibc_denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        let msg = ExecuteMsg::SetDenomAlias { denom: ibc_denom.to_string(), alias: Some("ATOM".to_string()) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        for funds in [vec![Coin::new(1000, "ujuno"), Coin::new(5, ibc_denom)], coins(1000, "ujuno")] {
+            let info = mock_info("creator", &funds);
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // The amount keeps the real denom, only the display name is aliased
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.amount[1].denom, ibc_denom);
+        assert_eq!(escrow.denom_display, Some("ujuno,ATOM".to_string()));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 2 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.denom_display, None);
+    }
+
+    #[test]
+    fn contract_cannot_take_part_in_escrow() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let contract = mock_env().contract.address.to_string();
+        let create = |beneficiary: &str, approver: &str| {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![(beneficiary.to_string(), 1)],
+                approvers: vec![approver.to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            (info, msg)
+        };
+
+        let (info, msg) = create(&contract, "approver1");
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidBeneficiary {}));
+
+        let (info, msg) = create("beneficiary", &contract);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidApprover {}));
+    }
+
+    #[test]
+    fn release_grace_period() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg {
+            two_phase_release: Some(true),
+            grace_period: Some(100),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        for _ in 0..2 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                arbiter: Some("arbiter".to_string()),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+        for escrow_id in [1, 2] {
+            let msg = ExecuteMsg::ApproveRelease { escrow_id, comment: None };
+            execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.release_eligible_at, Some(mock_env().block.time.seconds() + 100));
+
+        // Nobody can finalize during the grace period
+        let finalize = |escrow_id| ExecuteMsg::FinalizeRelease { escrow_id };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), finalize(1)).unwrap_err();
+        assert!(matches!(err, ContractError::TooEarly {}));
+
+        // A dispute raised during the window blocks the release
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("beneficiary", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), finalize(2)).unwrap_err();
+        assert!(matches!(err, ContractError::Disputed {}));
+
+        // Without a dispute anyone can finalize after the window
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), finalize(1)).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+    }
+
+    #[test]
+    fn cancellation_fee() {
+        let cancel = |msg: InstantiateMsg, funds: &[Coin]| -> Response {
+            let mut deps = mock_dependencies();
+            instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", funds), msg).unwrap();
+            let msg = ExecuteMsg::CancelEscrow { escrow_id: 1 };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap()
+        };
+        let sends = |res: &Response| -> Vec<(String, Vec<Coin>)> {
+            res.messages
+                .iter()
+                .map(|msg| match &msg.msg {
+                    CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => (to_address.clone(), amount.clone()),
+                    _ => panic!("Expected bank send"),
+                })
+                .collect()
+        };
+        let with_fee = || InstantiateMsg {
+            fee_recipient: Some("treasury".to_string()),
+            cancel_fee_bps: Some(100),
+            ..Default::default()
+        };
+
+        // 1% of the refund is kept for the fee recipient
+        let res = cancel(with_fee(), &coins(1000, "ujuno"));
+        assert_eq!(sends(&res), vec![("creator".to_string(), coins(990, "ujuno"))]);
+        assert!(res.attributes.contains(&Attribute::new("fee", "10ujuno")));
+
+        // The fee rounds down, never taking more than was escrowed
+        let res = cancel(with_fee(), &[Coin::new(199, "ujuno"), Coin::new(50, "uusdc")]);
+        assert_eq!(
+            sends(&res),
+            vec![("creator".to_string(), vec![Coin::new(198, "ujuno"), Coin::new(50, "uusdc")])]
+        );
+        assert!(res.attributes.contains(&Attribute::new("fee", "1ujuno")));
+
+        // No fee without a fee recipient or when it rounds to zero
+        let msg = InstantiateMsg { cancel_fee_bps: Some(100), ..Default::default() };
+        let res = cancel(msg, &coins(1000, "ujuno"));
+        assert_eq!(sends(&res), vec![("creator".to_string(), coins(1000, "ujuno"))]);
+        let res = cancel(with_fee(), &coins(99, "ujuno"));
+        assert_eq!(sends(&res), vec![("creator".to_string(), coins(99, "ujuno"))]);
+    }
+
+    #[test]
+    fn pending_approvals() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        for _ in 0..5 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string(), "approver2".to_string()],
+                description: "Test escrow".to_string(),
+                threshold: Some(2),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // approver1 approved escrow 1, escrow 2 was released and escrow 3 cancelled
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        for approver in ["approver1", "approver2"] {
+            let msg = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+            execute(deps.as_mut(), mock_env(), mock_info(approver, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 3 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let pending = |approver: &str, start_after: Option<u64>, limit: Option<u32>| -> Vec<u64> {
+            let msg = QueryMsg::GetPendingApprovals { approver: approver.to_string(), start_after, limit, sort: None };
+            let res: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+
+        assert_eq!(pending("approver1", None, None), vec![4, 5]);
+        assert_eq!(pending("approver2", None, None), vec![1, 4, 5]);
+        assert_eq!(pending("approver2", None, Some(2)), vec![1, 4]);
+        assert_eq!(pending("approver2", Some(4), Some(2)), vec![5]);
+    }
+
+    #[test]
+    fn create_on_behalf_of_creator() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // The contract itself cannot be recorded as the creator
+        let info = mock_info("custodian", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            creator: Some(mock_env().contract.address.to_string()),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidCreator {}));
+
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            creator: Some("client".to_string()),
+            ..Default::default()
+        });
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes[2].value, "client"); // creator
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.creator, Addr::unchecked("client"));
+
+        // Only the recorded creator can cancel, and the refund goes to them
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("custodian", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("client", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "client".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+    }
+
+    #[test]
+    fn migrate_version_guards() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Migrating to the current version again leaves the data untouched
+        let before = ESCROWS.load(deps.as_ref().storage, 1).unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(ESCROWS.load(deps.as_ref().storage, 1).unwrap(), before);
+
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cosmoscrow", "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrateVersion { .. }));
+
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:other", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrateContract { .. }));
+    }
+
+    #[test]
+    fn not_an_approver_error() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Outsiders approving get a distinct error from outsiders cancelling
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("outsider", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotAnApprover {}));
+
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("outsider", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // Non-approvers are rejected before the escrow state is looked at
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("outsider", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotAnApprover {}));
+    }
+
+    #[test]
+    fn payout_address_override() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        for _ in 0..2 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // Only the beneficiary can redirect their share
+        let msg = ExecuteMsg::SetPayoutAddress { escrow_id: 2, payout_address: "multisig".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("beneficiary", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 2 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.payout_overrides, vec![(Addr::unchecked("beneficiary"), Addr::unchecked("multisig"))]);
+
+        // Escrow 1 pays the beneficiary, escrow 2 the address they set
+        for (escrow_id, to_address) in [(1, "beneficiary"), (2, "multisig")] {
+            let msg = ExecuteMsg::ApproveRelease { escrow_id, comment: None };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+            assert_eq!(
+                res.messages[0].msg,
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: to_address.to_string(),
+                    amount: coins(1000, "ujuno"),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn escrow_history() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        for approver in ["approver1", "approver2"] {
+            env.block.time = env.block.time.plus_seconds(10);
+            let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+            execute(deps.as_mut(), env.clone(), mock_info(approver, &[]), msg).unwrap();
+        }
+
+        let msg = QueryMsg::GetEscrowHistory { escrow_id: 1, start_after: None, limit: None };
+        let history: EscrowHistoryResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let events: Vec<_> = history
+            .events
+            .iter()
+            .map(|event| (event.sequence, event.kind.clone(), event.actor.as_str(), event.timestamp))
+            .collect();
+        let created_at = mock_env().block.time.seconds();
+        assert_eq!(
+            events,
+            vec![
+                (0, EscrowEventKind::Created, "creator", created_at),
+                (1, EscrowEventKind::Approved, "approver1", created_at + 10),
+                (2, EscrowEventKind::Approved, "approver2", created_at + 20),
+                (3, EscrowEventKind::Released, "approver2", created_at + 20),
+            ]
+        );
+
+        // Pages continue after the last sequence number seen
+        let msg = QueryMsg::GetEscrowHistory { escrow_id: 1, start_after: Some(1), limit: Some(1) };
+        let history: EscrowHistoryResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(history.events.len(), 1);
+        assert_eq!(history.events[0].sequence, 2);
+    }
+
+    #[test]
+    fn get_escrows_by_ids() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        for _ in 0..3 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // Missing IDs are skipped, the others come back in the requested order
+        let msg = QueryMsg::GetEscrows { ids: vec![3, 7, 1, 0] };
+        let res: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let ids: Vec<u64> = res.escrows.iter().map(|escrow| escrow.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+
+        let msg = QueryMsg::GetEscrows { ids: (1..=31).collect() };
+        query(deps.as_ref(), mock_env(), msg).unwrap_err();
+    }
+
+    #[test]
+    fn approval_policies() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let approvers = |count: usize| (1..=count).map(|i| format!("approver{}", i)).collect::<Vec<_>>();
+        let cases = [
+            (ApprovalPolicy::TwoOfThree, 3, Some(2)),
+            (ApprovalPolicy::TwoOfThree, 2, None),
+            (ApprovalPolicy::TwoOfThree, 4, None),
+            (ApprovalPolicy::Unanimous, 4, Some(4)),
+            (ApprovalPolicy::Single, 1, Some(1)),
+            (ApprovalPolicy::Single, 2, None),
+            (ApprovalPolicy::Custom(3), 5, Some(3)),
+            (ApprovalPolicy::Custom(3), 2, None),
+            (ApprovalPolicy::Custom(0), 2, None),
+        ];
+        for (policy, approver_count, required) in cases {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: approvers(approver_count),
+                description: "Test escrow".to_string(),
+                policy: Some(policy.clone()),
+                ..Default::default()
+            });
+            match (execute(deps.as_mut(), mock_env(), info, msg), required) {
+                (Ok(res), Some(required)) => {
+                    let escrow_id = res.attributes[1].value.parse().unwrap();
+                    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id }).unwrap();
+                    let escrow: EscrowResponse = from_json(&res).unwrap();
+                    assert_eq!(escrow.threshold, required);
+                    assert_eq!(escrow.policy, Some(policy));
+                }
+                (Err(err), None) => assert!(matches!(err, ContractError::PolicyMismatch {})),
+                (res, _) => panic!("unexpected result for {:?}: {:?}", policy, res),
+            }
+        }
+
+        // A policy cannot be combined with another way of setting the threshold
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: approvers(3),
+            description: "Test escrow".to_string(),
+            threshold: Some(2),
+            policy: Some(ApprovalPolicy::TwoOfThree),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::PolicyMismatch {}));
+    }
+
+    #[test]
+    fn sweep_expired() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // Escrows 1, 2 and 4 expire after 100 seconds, escrow 3 after 1000 and 5 never
+        let now = mock_env().block.time.seconds();
+        for expires_at in [Some(now + 100), Some(now + 100), Some(now + 1000), Some(now + 100), None] {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                expires_at,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // A cancelled escrow leaves the expiration index right away, and so does a disputed one
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            expires_at: Some(now + 100),
+            arbiter: Some("arbiter".to_string()),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 6 };
+        execute(deps.as_mut(), mock_env(), mock_info("beneficiary", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(101);
+        let msg = ExecuteMsg::SweepExpired { limit: Some(1) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), msg.clone()).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.attributes[1], Attribute::new("swept", "1"));
+        assert_eq!(res.attributes[2], Attribute::new("escrow_ids", "1"));
+
+        // The next sweep continues with the remaining expired escrow
+        let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), msg.clone()).unwrap();
+        assert_eq!(res.attributes[2], Attribute::new("escrow_ids", "4"));
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), msg).unwrap();
+        assert_eq!(res.attributes[1], Attribute::new("swept", "0"));
+
+        let expected = [
+            (1, EscrowStatus::Expired),
+            (3, EscrowStatus::Open),
+            (5, EscrowStatus::Open),
+            (6, EscrowStatus::Open),
+        ];
+        for (escrow_id, status) in expected {
+            let res = query(deps.as_ref(), env.clone(), QueryMsg::GetEscrow { escrow_id }).unwrap();
+            let escrow: EscrowResponse = from_json(&res).unwrap();
+            assert_eq!(escrow.status, status);
+        }
+
+        // Only the escrow still waiting to expire is left to look at, the disputed one waits
+        // for its arbiter
+        let indexed: Vec<_> = ESCROWS_BY_EXPIRES_AT
+            .keys(&deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(indexed, vec![(now + 1000, 3)]);
+    }
+
+    #[test]
+    fn count_escrows() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let count = |deps: Deps, status: Option<EscrowStatus>| -> u64 {
+            from_json(query(deps, mock_env(), QueryMsg::CountEscrows { status }).unwrap()).unwrap()
+        };
+
+        for _ in 0..3 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+        assert_eq!(count(deps.as_ref(), None), 3);
+        assert_eq!(count(deps.as_ref(), Some(EscrowStatus::Open)), 3);
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        assert_eq!(count(deps.as_ref(), None), 3);
+        assert_eq!(count(deps.as_ref(), Some(EscrowStatus::Open)), 1);
+        assert_eq!(count(deps.as_ref(), Some(EscrowStatus::Released)), 1);
+        assert_eq!(count(deps.as_ref(), Some(EscrowStatus::Cancelled)), 1);
+        assert_eq!(count(deps.as_ref(), Some(EscrowStatus::Expired)), 0);
+    }
+
+    #[test]
+    fn creator_allowlist() {
+        let create = || {
+            ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            })
+        };
+
+        // Without an allowlist anyone can create escrows
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &coins(1000, "ujuno")), create()).unwrap();
+
+        // With one, only listed addresses can
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            creator_allowlist: Some(vec!["kyc_user".to_string()]),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("kyc_user", &coins(1000, "ujuno")), create()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &coins(1000, "ujuno")), create()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // Only the admin manages the allowlist
+        let msg = ExecuteMsg::UpdateCreatorAllowlist {
+            enabled: None,
+            add: vec!["anyone".to_string()],
+            remove: vec!["kyc_user".to_string()],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &coins(1000, "ujuno")), create()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("kyc_user", &coins(1000, "ujuno")), create()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // Turning the allowlist off opens creation to everyone again
+        let msg = ExecuteMsg::UpdateCreatorAllowlist { enabled: Some(false), add: vec![], remove: vec![] };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("kyc_user", &coins(1000, "ujuno")), create()).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let config: ConfigResponse = from_json(&res).unwrap();
+        assert!(!config.creator_allowlist_enabled);
+    }
+
+    #[test]
+    fn reconciliation() {
+        let mut deps = mock_dependencies_with_balance(&coins(1500, "ujuno"));
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // 500ujuno are held outside of any escrow
+        let msg = QueryMsg::GetReconciliation { denom: "ujuno".to_string() };
+        let res: ReconciliationResponse = from_json(query(deps.as_ref(), mock_env(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.locked_in_open_escrows.u128(), 1000);
+        assert_eq!(res.contract_balance.u128(), 1500);
+        assert_eq!(res.difference, Int256::from(500i32));
+
+        // A balance below the locked amount shows up as a negative difference
+        deps.querier.update_balance(mock_env().contract.address, coins(400, "ujuno"));
+        let res: ReconciliationResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.difference, Int256::from(-600i32));
+
+        let msg = QueryMsg::GetReconciliation { denom: "uatom".to_string() };
+        let res: ReconciliationResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.difference, Int256::zero());
+    }
+
+    #[test]
+    fn approval_ttl() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            approval_ttl: Some(100),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let approve = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve.clone()).unwrap();
+
+        // Within the TTL the approval cannot be given again
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyApproved {}));
+
+        // approver1's approval is stale by the time approver2 approves
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(200);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("approver2", &[]), approve.clone()).unwrap();
+        assert!(res.messages.is_empty());
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetApprovalStatus { escrow_id: 1 }).unwrap();
+        let status: ApprovalStatusResponse = from_json(&res).unwrap();
+        assert_eq!(status.current, 1);
+        assert_eq!(status.pending, vec![Addr::unchecked("approver1")]);
+
+        // Renewing it releases the funds
+        let res = execute(deps.as_mut(), env.clone(), mock_info("approver1", &[]), approve).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+        let res = query(deps.as_ref(), env, QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.approvals.len(), 2);
+    }
+
+    #[test]
+    fn approval_comment() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+            description: "Test escrow".to_string(),
+            threshold: Some(3),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: Some("x".repeat(281)) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::CommentTooLong {}));
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: Some("Goods received".to_string()) };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert_eq!(escrow.approvals[0].comment.as_deref(), Some("Goods received"));
+        assert_eq!(escrow.approvals[1].comment, None);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetApprovalStatus { escrow_id: 1 }).unwrap();
+        let status: ApprovalStatusResponse = from_json(&res).unwrap();
+        assert_eq!(status.comments, vec![(Addr::unchecked("approver1"), "Goods received".to_string())]);
+    }
+
+    #[test]
+    fn would_release() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+            description: "Test escrow".to_string(),
+            threshold: Some(3),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let would_release = |deps: Deps, approver: &str| -> bool {
+            let msg = QueryMsg::WouldRelease { escrow_id: 1, approver: approver.to_string() };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // Two approvals short of the threshold
+        assert!(!would_release(deps.as_ref(), "approver1"));
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert!(!would_release(deps.as_ref(), "approver2"));
+
+        // At threshold minus one only approvers that did not approve yet would release
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap();
+        assert!(would_release(deps.as_ref(), "approver3"));
+        assert!(!would_release(deps.as_ref(), "approver1"));
+        assert!(!would_release(deps.as_ref(), "creator"));
+        assert!(!would_release(deps.as_ref(), "outsider"));
+    }
+
+    #[test]
+    fn unfunded_escrow_lifecycle() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        };
+        let msg = ExecuteMsg::CreateUnfundedEscrow(create.clone());
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap_err();
+        assert!(matches!(err, ContractError::UnexpectedFunds {}));
+        let msg = ExecuteMsg::CreateUnfundedEscrow(create);
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(&res).unwrap();
+        assert!(!escrow.funded);
+        assert!(escrow.amount.is_empty());
+
+        // Approvals wait for the funds
+        let approve = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NotFunded {}));
+
+        // Only the creator funds the escrow, and only once
+        let fund = ExecuteMsg::FundEscrow { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("outsider", &coins(1000, "ujuno")), fund.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), fund.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), fund).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyFunded {}));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetStats {}).unwrap();
+        let stats: StatsResponse = from_json(&res).unwrap();
+        assert_eq!(stats.total_locked, coins(1000, "ujuno"));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "beneficiary".to_string(),
+                amount: coins(1000, "ujuno"),
+            })
+        );
+    }
+
+    #[test]
+    fn cancel_unfunded_escrow() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg::default();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateUnfundedEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // There is nothing to refund
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+    }
+
+    #[test]
+    fn creation_rate_limit() {
+        // An empty window or no allowed creations would make the limit meaningless
+        let mut deps = mock_dependencies();
+        for rate_limit in [RateLimit { max_creations: 2, window: 0 }, RateLimit { max_creations: 0, window: 60 }] {
+            let msg = InstantiateMsg { rate_limit: Some(rate_limit), ..Default::default() };
+            let err = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidRateLimit {}));
+        }
+
+        let msg = InstantiateMsg {
+            rate_limit: Some(RateLimit { max_creations: 2, window: 60 }),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = || {
+            ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            })
+        };
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+
+        // The third creation in the window is rejected, other addresses are not affected
+        let mut env = mock_env();
+        execute(deps.as_mut(), env.clone(), info.clone(), create()).unwrap();
+        env.block.time = env.block.time.plus_seconds(30);
+        execute(deps.as_mut(), env.clone(), info.clone(), create()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), create()).unwrap_err();
+        assert!(matches!(err, ContractError::RateLimited {}));
+        execute(deps.as_mut(), env.clone(), mock_info("other", &coins(1000, "ujuno")), create()).unwrap();
+
+        // A new window starts once the first one ended
+        env.block.time = env.block.time.plus_seconds(30);
+        execute(deps.as_mut(), env.clone(), info.clone(), create()).unwrap();
+
+        // The admin can lift the limit
+        execute(deps.as_mut(), env.clone(), info.clone(), create()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), create()).unwrap_err();
+        assert!(matches!(err, ContractError::RateLimited {}));
+        let msg = ExecuteMsg::UpdateRateLimit { rate_limit: None };
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        execute(deps.as_mut(), env.clone(), info.clone(), create()).unwrap();
+
+        let rate_limit = Some(RateLimit { max_creations: 0, window: 60 });
+        let msg = ExecuteMsg::UpdateRateLimit { rate_limit };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRateLimit {}));
+
+        // A window running past the end of time never ends rather than overflowing
+        let rate_limit = Some(RateLimit { max_creations: 1, window: u64::MAX });
+        let msg = ExecuteMsg::UpdateRateLimit { rate_limit };
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let info = mock_info("fresh", &coins(1000, "ujuno"));
+        execute(deps.as_mut(), env.clone(), info.clone(), create()).unwrap();
+        let err = execute(deps.as_mut(), env, info, create()).unwrap_err();
+        assert!(matches!(err, ContractError::RateLimited {}));
+    }
+
+    #[test]
+    fn helper_messages() {
+        let contract = CwTemplateContract(Addr::unchecked("escrow"));
+        let expect = |msg: CosmosMsg, expected: ExecuteMsg, funds: Vec<Coin>| {
+            assert_eq!(
+                msg,
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "escrow".to_string(),
+                    msg: to_json_binary(&expected).unwrap(),
+                    funds,
+                })
+            );
+        };
+
+        let create = CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        };
+        let msg = contract.create_escrow(create.clone(), coins(1000, "ujuno")).unwrap();
+        expect(msg, ExecuteMsg::CreateEscrow(create), coins(1000, "ujuno"));
+
+        let msg = contract.approve_release(1).unwrap();
+        expect(msg, ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None }, vec![]);
+
+        let msg = contract.cancel_escrow(2).unwrap();
+        expect(msg, ExecuteMsg::CancelEscrow { escrow_id: 2 }, vec![]);
+    }
+
+    #[test]
+    fn call_with_funds_attaches_coins() {
+        let contract = CwTemplateContract(Addr::unchecked("escrow"));
+        let msg = ExecuteMsg::FundEscrow { escrow_id: 1 };
+        let funds = vec![Coin::new(500, "ujuno"), Coin::new(20, "uusdc")];
+
+        match contract.call_with_funds(msg.clone(), funds.clone()).unwrap() {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg: binary, funds: attached }) => {
+                assert_eq!(contract_addr, "escrow");
+                assert_eq!(binary, to_json_binary(&msg).unwrap());
+                assert_eq!(attached, funds);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+
+        // call keeps sending no funds
+        match contract.call(msg).unwrap() {
+            CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => assert!(funds.is_empty()),
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn all_escrows_pagination_is_stable() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        for _ in 0..5 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let page = |deps: Deps, include_completed: Option<bool>, start_after: Option<u64>| -> Vec<u64> {
+            let msg =
+                QueryMsg::GetAllEscrows { status: None, include_completed, start_after, limit: Some(2), order: None };
+            let res: EscrowListResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+
+        // Cancelling escrows on both sides of the cursor neither skips nor repeats entries
+        assert_eq!(page(deps.as_ref(), None, None), vec![1, 2]);
+        for escrow_id in [2, 3] {
+            let msg = ExecuteMsg::CancelEscrow { escrow_id };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        }
+        assert_eq!(page(deps.as_ref(), None, Some(2)), vec![3, 4]);
+        assert_eq!(page(deps.as_ref(), None, Some(4)), vec![5]);
+
+        // Completed escrows can be left out, the cursor is still the last returned ID
+        assert_eq!(page(deps.as_ref(), Some(false), None), vec![1, 4]);
+        assert_eq!(page(deps.as_ref(), Some(false), Some(4)), vec![5]);
+        assert_eq!(page(deps.as_ref(), Some(true), Some(1)), vec![2, 3]);
+    }
+
+    #[test]
+    fn emergency_withdraw_timelock() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Only the admin can propose and execute
+        let propose = ExecuteMsg::ProposeEmergencyWithdraw { escrow_id: 1, to: "recovery".to_string() };
+        let withdraw = ExecuteMsg::ExecuteEmergencyWithdraw { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), withdraw.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NoEmergencyWithdraw {}));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), propose).unwrap();
+        let executable_at = mock_env().block.time.seconds() + 7 * 24 * 60 * 60;
+        assert_eq!(res.events[0].ty, "emergency_withdraw_proposed");
+        assert!(res.events[0].attributes.contains(&Attribute::new("executable_at", executable_at.to_string())));
+
+        // The withdrawal is locked for 7 days
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(7 * 24 * 60 * 60 - 1);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), withdraw.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::EmergencyWithdrawLocked { executable_at: at } if at == executable_at));
+
+        env.block.time = env.block.time.plus_seconds(1);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), withdraw.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), withdraw.clone()).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send { to_address: "recovery".to_string(), amount: coins(1000, "ujuno") })
+        );
+        assert_eq!(res.events[0].ty, "emergency_withdraw_executed");
+
+        let msg = QueryMsg::GetEscrow { escrow_id: 1 };
+        let escrow: EscrowResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(escrow.status, EscrowStatus::Cancelled);
+
+        // The proposal is used up
+        let err = execute(deps.as_mut(), env, mock_info("admin", &[]), withdraw).unwrap_err();
+        assert!(matches!(err, ContractError::NoEmergencyWithdraw {}));
+    }
+
+    #[test]
+    fn weighted_approvals() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let create = |approver_weights: Option<Vec<u64>>, required_weight: Option<u64>| {
+            ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["lead".to_string(), "approver2".to_string(), "approver3".to_string()],
+                description: "Test escrow".to_string(),
+                approver_weights,
+                required_weight,
+                ..Default::default()
+            })
+        };
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+
+        // Weights must be non-zero, one per approver, and able to reach the required weight
+        for msg in [
+            create(Some(vec![2, 1]), Some(2)),
+            create(Some(vec![2, 0, 1]), Some(2)),
+            create(Some(vec![2, 1, 1]), Some(5)),
+            create(Some(vec![2, 1, 1]), Some(0)),
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidWeights {}));
+        }
+        let mut msg = create(Some(vec![2, 1, 1]), Some(2));
+        if let ExecuteMsg::CreateEscrow(create_msg) = &mut msg {
+            create_msg.threshold = Some(1);
+        }
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidWeights {}));
+
+        // The lead signer counts double and meets the required weight alone
+        execute(deps.as_mut(), mock_env(), info.clone(), create(Some(vec![2, 1, 1]), Some(2))).unwrap();
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("lead", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // Lighter approvers need each other
+        execute(deps.as_mut(), mock_env(), info.clone(), create(Some(vec![2, 1, 1]), Some(2))).unwrap();
+        let msg = QueryMsg::WouldRelease { escrow_id: 2, approver: "approver2".to_string() };
+        assert!(!from_json::<bool>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap());
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+        let msg = QueryMsg::GetApprovalStatus { escrow_id: 2 };
+        let status: ApprovalStatusResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!((status.current_weight, status.required_weight), (1, 2));
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver3", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // Without weights every approver counts once and the threshold is the required weight
+        execute(deps.as_mut(), mock_env(), info, create(None, None)).unwrap();
+        let msg = QueryMsg::GetEscrow { escrow_id: 3 };
+        let escrow: EscrowResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(escrow.approver_weights, vec![1, 1, 1]);
+        assert_eq!(escrow.required_weight, 2);
+        assert_eq!(escrow.threshold, 2);
+    }
+
+    #[test]
+    fn cancel_all_my_escrows() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        for creator in ["creator", "creator", "creator", "other", "creator"] {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string(), "approver2".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info(creator, &coins(1000, "ujuno")), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        let cancel_all =
+            |start_after: Option<u64>, limit: Option<u32>| ExecuteMsg::CancelAllMyEscrows { start_after, limit };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), cancel_all(None, Some(1))).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("escrow_ids", "1")));
+        assert!(res.attributes.contains(&Attribute::new("last_checked", "1")));
+        assert_eq!(res.messages.len(), 1);
+
+        // The approved escrow is skipped, but still counts towards the limit
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), cancel_all(Some(1), Some(2))).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("cancelled", "1")));
+        assert!(res.attributes.contains(&Attribute::new("escrow_ids", "3")));
+        assert!(res.attributes.contains(&Attribute::new("skipped", "2")));
+        assert!(res.attributes.contains(&Attribute::new("last_checked", "3")));
+
+        // Starting over passes the cancelled escrows and skips the approved one again
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), cancel_all(None, None)).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("cancelled", "1")));
+        assert!(res.attributes.contains(&Attribute::new("escrow_ids", "5")));
+        assert!(res.attributes.contains(&Attribute::new("skipped", "2")));
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send { to_address: "creator".to_string(), amount: coins(1000, "ujuno") })
+        );
+
+        let status = |deps: Deps, escrow_id: u64| -> EscrowStatus {
+            let msg = QueryMsg::GetEscrow { escrow_id };
+            from_json::<EscrowResponse>(query(deps, mock_env(), msg).unwrap()).unwrap().status
+        };
+        assert_eq!(status(deps.as_ref(), 2), EscrowStatus::Open);
+        assert_eq!(status(deps.as_ref(), 4), EscrowStatus::Open);
+        assert_eq!(status(deps.as_ref(), 5), EscrowStatus::Cancelled);
+
+        // Nothing left to cancel, or to look at after the last escrow
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), cancel_all(None, None)).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("cancelled", "0")));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), cancel_all(Some(5), None)).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("last_checked", "")));
+    }
+
+    #[test]
+    fn capabilities_reflect_config() {
+        let capabilities = |deps: Deps| -> CapabilitiesResponse {
+            from_json(query(deps, mock_env(), QueryMsg::GetCapabilities {}).unwrap()).unwrap()
+        };
+
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+        let res = capabilities(deps.as_ref());
+        assert_eq!(res.contract, "crates.io:cosmoscrow");
+        assert_eq!(res.version, env!("CARGO_PKG_VERSION"));
+        assert!(res.accepting_escrows);
+        assert!(!res.release_fee && !res.cancel_fee && !res.denom_allowlist && !res.min_amounts);
+        assert!(!res.require_acceptance && !res.two_phase_release && !res.creator_allowlist && !res.rate_limit);
+
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            fee_recipient: Some("treasury".to_string()),
+            fee_bps: Some(100),
+            allowed_denoms: Some(vec!["ujuno".to_string()]),
+            min_amounts: Some(coins(10, "ujuno")),
+            require_acceptance: Some(true),
+            two_phase_release: Some(true),
+            grace_period: Some(3600),
+            creator_allowlist: Some(vec!["creator".to_string()]),
+            rate_limit: Some(RateLimit { max_creations: 5, window: 60 }),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::SetPaused { paused: true }).unwrap();
+        let res = capabilities(deps.as_ref());
+        assert!(!res.accepting_escrows);
+        assert!(res.release_fee && !res.cancel_fee);
+        assert_eq!(res.fee_bps, 100);
+        assert!(res.denom_allowlist && res.min_amounts && res.require_acceptance && res.two_phase_release);
+        assert_eq!(res.grace_period, 3600);
+        assert!(res.creator_allowlist && res.rate_limit);
+    }
+
+    #[test]
+    fn replace_approver() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "compromised".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+
+        let replace = |old: &str, new: &str| ExecuteMsg::ReplaceApprover {
+            escrow_id: 1,
+            old: old.to_string(),
+            new: new.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), replace("compromised", "fresh"))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), replace("stranger", "fresh"))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidApprover {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), replace("compromised", "approver1"))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateApprover {}));
+
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), replace("compromised", "fresh")).unwrap();
+
+        let approver_ids = |deps: Deps, address: &str| -> Vec<u64> {
+            let msg = QueryMsg::GetEscrowsByAddress {
+                address: address.to_string(),
+                status: None,
+                role: Some(EscrowRole::Approver),
+                start_after: None,
+                limit: None,
+            };
+            let res: EscrowListResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+        assert!(approver_ids(deps.as_ref(), "compromised").is_empty());
+        assert_eq!(approver_ids(deps.as_ref(), "fresh"), vec![1]);
+
+        // The old approver is out, the new one can approve
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("compromised", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NotAnApprover {}));
+        execute(deps.as_mut(), mock_env(), mock_info("fresh", &[]), msg).unwrap();
+
+        // After an approval the approvers are fixed
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), replace("approver1", "other"))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn default_threshold_policy() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { default_threshold_policy: Some(ApprovalMode::Unanimous), ..Default::default() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, threshold: Option<u32>| {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+                description: "Test escrow".to_string(),
+                threshold,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        };
+        let threshold = |deps: Deps, escrow_id: u64| -> u32 {
+            let msg = QueryMsg::GetEscrow { escrow_id };
+            from_json::<EscrowResponse>(query(deps, mock_env(), msg).unwrap()).unwrap().threshold
+        };
+
+        create(&mut deps, None);
+        assert_eq!(threshold(deps.as_ref(), 1), 3);
+
+        // Only the admin can change the default, which only applies to new escrows
+        let msg = ExecuteMsg::UpdateDefaultThresholdPolicy { policy: Some(ApprovalMode::Single) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        create(&mut deps, None);
+        assert_eq!(threshold(deps.as_ref(), 1), 3);
+        assert_eq!(threshold(deps.as_ref(), 2), 1);
+
+        // An explicit threshold still wins
+        create(&mut deps, Some(2));
+        assert_eq!(threshold(deps.as_ref(), 3), 2);
+
+        // Without a default, escrows fall back to a simple majority
+        let msg = ExecuteMsg::UpdateDefaultThresholdPolicy { policy: None };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        create(&mut deps, None);
+        assert_eq!(threshold(deps.as_ref(), 4), 2);
+        assert_eq!(threshold(deps.as_ref(), 2), 1);
+    }
+
+    #[test]
+    fn required_approvals_frozen_at_creation() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { default_threshold_policy: Some(ApprovalMode::Unanimous), ..Default::default() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        assert_eq!(ESCROWS.load(deps.as_ref().storage, 1).unwrap().required_approvals, 3);
+
+        // Changing the config later leaves the requirement of the existing escrow alone
+        let msg = ExecuteMsg::UpdateDefaultThresholdPolicy { policy: Some(ApprovalMode::Single) };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = QueryMsg::GetApprovalStatus { escrow_id: 1 };
+        let status: ApprovalStatusResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(status.required, 3);
+
+        // Escrows stored before v0.7.0 get their requirement fixed when migrating
+        let mut escrow = ESCROWS.load(deps.as_ref().storage, 1).unwrap();
+        escrow.required_approvals = 0;
+        escrow.threshold = None;
+        ESCROWS.save(deps.as_mut().storage, 1, &escrow).unwrap();
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cosmoscrow", "0.6.0").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(ESCROWS.load(deps.as_ref().storage, 1).unwrap().required_approvals, 2);
+    }
+
+    #[test]
+    fn actionable_escrows() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { two_phase_release: Some(true), ..Default::default() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let mut create = |creator: &str, beneficiary: &str, approvers: &[&str], funded: bool| {
+            let msg = CreateEscrowMsg {
+                beneficiaries: vec![(beneficiary.to_string(), 1)],
+                approvers: approvers.iter().map(|approver| approver.to_string()).collect(),
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            };
+            let (msg, funds) = match funded {
+                true => (ExecuteMsg::CreateEscrow(msg), coins(1000, "ujuno")),
+                false => (ExecuteMsg::CreateUnfundedEscrow(msg), vec![]),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(creator, &funds), msg).unwrap();
+        };
+        create("creator", "bob", &["alice"], true);
+        create("creator", "alice", &["approver1"], true);
+        create("alice", "bob", &["approver1"], true);
+        create("alice", "bob", &["approver1"], false);
+        create("creator", "bob", &["alice", "approver1"], true);
+        create("alice", "bob", &["approver1"], true);
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 5, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 6, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        let actionable = |start_after: Option<u64>, limit: Option<u32>| -> Vec<(u64, Vec<EscrowAction>)> {
+            let msg = QueryMsg::GetActionable { address: "alice".to_string(), start_after, limit };
+            let res: ActionableResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| (e.escrow.id, e.actions)).collect()
+        };
+        assert_eq!(
+            actionable(None, None),
+            vec![
+                (1, vec![EscrowAction::Approve]),
+                (2, vec![EscrowAction::Accept]),
+                (3, vec![EscrowAction::Cancel]),
+                (4, vec![EscrowAction::Fund, EscrowAction::Cancel]),
+                (6, vec![EscrowAction::Finalize]),
+            ]
+        );
+        assert_eq!(
+            actionable(Some(2), Some(2)),
+            vec![(3, vec![EscrowAction::Cancel]), (4, vec![EscrowAction::Fund, EscrowAction::Cancel])]
+        );
+    }
+
+    #[test]
+    fn release_hook() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let hook_msg = to_json_binary(&"mint_receipt").unwrap();
+        let create = |release_hook: Option<&str>, release_hook_msg: Option<Binary>| {
+            ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                release_hook: release_hook.map(String::from),
+                release_hook_msg,
+                ..Default::default()
+            })
+        };
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+
+        // The hook needs both a contract and a message, and cannot be the escrow contract
+        for msg in [
+            create(Some("receipts"), None),
+            create(None, Some(hook_msg.clone())),
+            create(Some("cosmos2contract"), Some(hook_msg.clone())),
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidReleaseHook {}));
+        }
+
+        execute(deps.as_mut(), mock_env(), info, create(Some("receipts"), Some(hook_msg.clone()))).unwrap();
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        // The hook runs after the payout, and neither send is caught by a reply
+        assert_eq!(res.messages.len(), 2);
+        assert!(res.messages.iter().all(|msg| msg.reply_on == ReplyOn::Never));
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send { to_address: "beneficiary".to_string(), amount: coins(1000, "ujuno") })
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr: "receipts".to_string(), msg: hook_msg, funds: vec![] })
+        );
+    }
+
+    #[test]
+    fn release_rejects_corrupted_amount() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+
+        // Seed a zero amount, as a faulty migration could leave behind
+        let mut escrow = ESCROWS.load(deps.as_ref().storage, 1).unwrap();
+        escrow.amount = coins(0, "ujuno");
+        ESCROWS.save(deps.as_mut().storage, 1, &escrow).unwrap();
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::ConditionsNotMet {}));
+        assert_eq!(ESCROWS.load(deps.as_ref().storage, 1).unwrap().status, EscrowStatus::Open);
+    }
+
+    #[test]
+    fn stale_escrows() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { two_phase_release: Some(true), ..Default::default() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // Escrows created 0, 100, 200 and 300 seconds after the start
+        let mut env = mock_env();
+        for _ in 0..4 {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+            env.block.time = env.block.time.plus_seconds(100);
+        }
+
+        // Cancelled and fully approved escrows no longer wait for anyone
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 1 };
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+        execute(deps.as_mut(), env.clone(), mock_info("approver1", &[]), msg).unwrap();
+
+        let stale = |older_than_secs: u64, start_after: Option<u64>, limit: Option<u32>| -> Vec<u64> {
+            let msg = QueryMsg::GetStaleEscrows { older_than_secs, start_after, limit };
+            let res: EscrowListResponse = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+        assert_eq!(stale(0, None, None), vec![3, 4]);
+        assert_eq!(stale(150, None, None), vec![3]);
+        assert_eq!(stale(200, None, None), Vec::<u64>::new());
+        assert_eq!(stale(0, None, Some(1)), vec![3]);
+        assert_eq!(stale(0, Some(3), None), vec![4]);
+    }
+
+    #[test]
+    fn approval_tiers() {
+        let tier = |min_amount: u128| ApprovalTier {
+            denom: "ujuno".to_string(),
+            min_amount: Uint128::new(min_amount),
+            mode: ApprovalMode::Unanimous,
+        };
+
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { approval_tiers: Some(vec![tier(0)]), ..Default::default() };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidApprovalTier {}));
+        let msg = InstantiateMsg { approval_tiers: Some(vec![tier(1_000_000)]), ..Default::default() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let create = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, funds: Vec<Coin>| {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &funds), msg).unwrap();
+        };
+        let required = |deps: Deps, escrow_id: u64| ESCROWS.load(deps.storage, escrow_id).unwrap().required_approvals;
+
+        // Just below the tier the simple majority applies, from the tier on every approver
+        create(&mut deps, coins(999_999, "ujuno"));
+        create(&mut deps, coins(1_000_000, "ujuno"));
+        create(&mut deps, coins(5_000_000, "uatom"));
+        assert_eq!(required(deps.as_ref(), 1), 2);
+        assert_eq!(required(deps.as_ref(), 2), 3);
+        assert_eq!(required(deps.as_ref(), 3), 2);
+
+        // Topping up into the tier raises the requirement
+        let msg = ExecuteMsg::TopUp { escrow_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1, "ujuno")), msg).unwrap();
+        assert_eq!(required(deps.as_ref(), 1), 3);
+
+        // New tiers only apply to new escrows
+        let msg = ExecuteMsg::UpdateApprovalTiers { tiers: vec![] };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        create(&mut deps, coins(1_000_000, "ujuno"));
+        assert_eq!(required(deps.as_ref(), 2), 3);
+        assert_eq!(required(deps.as_ref(), 4), 2);
+    }
+
+    #[test]
+    fn escrow_not_found() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 7, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowNotFound {}));
+
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 7 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowNotFound {}));
+
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 7 }).unwrap_err();
+        assert_eq!(err.to_string(), "Escrow 7 not found");
+    }
+
+    #[test]
+    fn approvals_pagination() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let approvers: Vec<String> = (0..10).map(|i| format!("approver{}", i)).collect();
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: approvers.clone(),
+            description: "Test escrow".to_string(),
+            threshold: Some(10),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Approve in reverse so that approval order differs from address order
+        for approver in approvers.iter().rev().take(9) {
+            let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: Some(format!("ok from {}", approver)) };
+            execute(deps.as_mut(), mock_env(), mock_info(approver, &[]), msg).unwrap();
+        }
+
+        let mut seen = vec![];
+        let mut start_after = None;
+        loop {
+            let msg = QueryMsg::GetApprovals { escrow_id: 1, start_after: start_after.clone(), limit: Some(4) };
+            let page: ApprovalListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            if page.approvals.is_empty() {
+                break;
+            }
+            assert!(page.approvals.len() <= 4);
+            start_after = page.approvals.last().map(|approval| approval.approver.to_string());
+            seen.extend(page.approvals);
+        }
+
+        let seen_approvers: Vec<String> = seen.iter().map(|approval| approval.approver.to_string()).collect();
+        assert_eq!(seen_approvers, approvers[1..].to_vec());
+        assert_eq!(seen[0].comment, Some("ok from approver1".to_string()));
+        assert_eq!(seen[0].approved_at, mock_env().block.time.seconds());
+
+        let msg = QueryMsg::GetApprovals { escrow_id: 2, start_after: None, limit: None };
+        let err = query(deps.as_ref(), mock_env(), msg).unwrap_err();
+        assert_eq!(err.to_string(), "Escrow 2 not found");
+    }
+
+    #[test]
+    fn empty_approver_set() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec![],
+            description: "Test escrow".to_string(),
+            threshold: Some(0),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::NoApprovers {}));
+
+        let msg = ExecuteMsg::SaveTemplate {
+            name: "empty".to_string(),
+            approvers: vec![],
+            threshold: None,
+            description: "Template".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NoApprovers {}));
+        assert_eq!(ESCROW_COUNTER.may_load(deps.as_ref().storage).unwrap().unwrap_or_default(), 0);
+    }
+
+    #[test]
+    fn escrows_by_denom() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let create = CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        };
+        let mut both = coins(1000, "ujuno");
+        both.push(Coin::new(500, "uusdc"));
+        for funds in [coins(1000, "ujuno"), coins(1000, "uusdc"), both, coins(1000, "ujuno")] {
+            let msg = ExecuteMsg::CreateEscrow(create.clone());
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &funds), msg).unwrap();
+        }
+
+        // Unfunded escrows are indexed once they are funded
+        let msg = ExecuteMsg::CreateUnfundedEscrow(create);
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::FundEscrow { escrow_id: 5 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "uusdc")), msg).unwrap();
+
+        // Cancelled escrows stay in the index
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 4 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let by_denom = |deps: &OwnedDeps<_, _, _>, denom: &str, start_after: Option<u64>| -> Vec<u64> {
+            let msg = QueryMsg::GetEscrowsByDenom { denom: denom.to_string(), start_after, limit: Some(2) };
+            let res: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.escrows.iter().map(|escrow| escrow.id).collect()
+        };
+        assert_eq!(by_denom(&deps, "ujuno", None), vec![1, 3]);
+        assert_eq!(by_denom(&deps, "ujuno", Some(3)), vec![4]);
+        assert_eq!(by_denom(&deps, "uusdc", None), vec![2, 3]);
+        assert_eq!(by_denom(&deps, "uusdc", Some(3)), vec![5]);
+        assert_eq!(by_denom(&deps, "uatom", None), Vec::<u64>::new());
+
+        // Escrows stored before v0.8.0 are indexed when migrating
+        ESCROWS_BY_DENOM.clear(deps.as_mut().storage);
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cosmoscrow", "0.7.0").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(by_denom(&deps, "ujuno", None), vec![1, 3]);
+        assert_eq!(by_denom(&deps, "uusdc", Some(2)), vec![3, 5]);
+    }
+
+    #[test]
+    fn completed_escrows_stay_indexed() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        for _ in 0..2 {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let storage = deps.as_ref().storage;
+        for id in [1, 2] {
+            assert!(ESCROWS_BY_CREATOR.has(storage, (&Addr::unchecked("creator"), id)));
+            assert!(ESCROWS_BY_BENEFICIARY.has(storage, (&Addr::unchecked("beneficiary"), id)));
+            assert!(ESCROWS_BY_APPROVER.has(storage, (&Addr::unchecked("approver1"), id)));
+            assert!(ESCROWS_BY_DENOM.has(storage, ("ujuno", id)));
+        }
+
+        let msg = QueryMsg::GetEscrowsByAddress {
+            address: "creator".to_string(),
+            status: None,
+            role: None,
+            start_after: None,
+            limit: None,
+        };
+        let list: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let statuses: Vec<EscrowStatus> = list.escrows.into_iter().map(|escrow| escrow.status).collect();
+        assert_eq!(statuses, vec![EscrowStatus::Released, EscrowStatus::Cancelled]);
+
+        // Completed escrows are passed over without being reported as skipped
+        let msg = ExecuteMsg::CancelAllMyEscrows { start_after: None, limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("cancelled", "0")));
+        assert!(res.attributes.contains(&Attribute::new("skipped", "")));
+    }
+
+    #[test]
+    fn signed_approval() {
+        use crate::helpers::approval_sign_bytes;
+        use k256::ecdsa::signature::hazmat::PrehashSigner;
+        use k256::ecdsa::{Signature, SigningKey};
+        use sha2::{Digest, Sha256};
+
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            threshold: Some(1),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+
+        let sign_nonce = |key: &SigningKey, escrow_id: u64, approver: &str, nonce: u64| -> Binary {
+            let contract = mock_env().contract.address;
+            let hash = Sha256::digest(approval_sign_bytes(&contract, escrow_id, &Addr::unchecked(approver), nonce));
+            let signature: Signature = key.sign_prehash(&hash).unwrap();
+            Binary::from(signature.to_bytes().as_slice())
+        };
+        let sign = |key: &SigningKey, escrow_id: u64, approver: &str| sign_nonce(key, escrow_id, approver, 0);
+        let signed_for = |escrow_id: u64, approver: &str, signature: Binary| ExecuteMsg::ApproveReleaseSigned {
+            escrow_id,
+            approver: approver.to_string(),
+            signature,
+        };
+        let signed = |approver: &str, signature: Binary| signed_for(1, approver, signature);
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let forger = SigningKey::from_slice(&[9u8; 32]).unwrap();
+
+        // Without a registered key nothing can be verified
+        let msg = signed("approver1", sign(&key, 1, "approver1"));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSignature {}));
+
+        let msg = ExecuteMsg::SetApprovalKey { pubkey: Some(Binary::from(vec![2u8; 20])) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPublicKey {}));
+        let pubkey = Binary::from(key.verifying_key().to_encoded_point(true).as_bytes());
+        let msg = ExecuteMsg::SetApprovalKey { pubkey: Some(pubkey) };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        // Forged signatures, and signatures for another escrow or approver, are rejected
+        for signature in [sign(&forger, 1, "approver1"), sign(&key, 2, "approver1"), sign(&key, 1, "approver2")] {
+            let msg = signed("approver1", signature);
+            let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidSignature {}));
+        }
+
+        // A valid signature approves on the approver's behalf, which releases the escrow
+        let msg = signed("approver1", sign(&key, 1, "approver1"));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("approver", "approver1")));
+        assert!(res.attributes.contains(&Attribute::new("relayer", "relayer")));
+        assert!(res.attributes.contains(&Attribute::new("released", "true")));
+        let escrow = ESCROWS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(escrow.approvals[0].approver, Addr::unchecked("approver1"));
+
+        // The same signature cannot be submitted again
+        let msg = signed("approver1", sign(&key, 1, "approver1"));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSignature {}));
+
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            threshold: Some(2),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        let nonce = |deps: Deps| -> u64 {
+            let msg = QueryMsg::GetApprovalNonce { escrow_id: 2, approver: "approver1".to_string() };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let approve = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+        let revoke = ExecuteMsg::RevokeApproval { escrow_id: 2 };
+
+        // A signature handed out but never submitted goes stale once the approver approves
+        // and revokes on their own
+        let stale = sign_nonce(&key, 2, "approver1", nonce(deps.as_ref()));
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), revoke.clone()).unwrap();
+        assert_eq!(nonce(deps.as_ref()), 2);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), signed_for(2, "approver1", stale))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSignature {}));
+
+        // A fresh signature works, and again after the signed approval was revoked
+        for _ in 0..2 {
+            let signature = sign_nonce(&key, 2, "approver1", nonce(deps.as_ref()));
+            let msg = signed_for(2, "approver1", signature);
+            execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+            assert!(ESCROWS.load(deps.as_ref().storage, 2).unwrap().has_approved(&Addr::unchecked("approver1")));
+            execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), revoke.clone()).unwrap();
+        }
+    }
+
+    #[test]
+    fn remainder_policies() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        // 100 split 1:2:4 is 14.29, 28.57 and 57.14, leaving 1 over after rounding down.
+        // Bob's share was rounded down the most.
+        let cases = [
+            (None, [15, 28, 57]),
+            (Some(RemainderPolicy::FirstBeneficiary), [15, 28, 57]),
+            (Some(RemainderPolicy::LastBeneficiary), [14, 28, 58]),
+            (Some(RemainderPolicy::ProRataLargest), [14, 29, 57]),
+        ];
+        for (escrow_id, (remainder_policy, expected)) in (1..).zip(cases) {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("alice".to_string(), 1), ("bob".to_string(), 2), ("carol".to_string(), 4)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                remainder_policy,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(100, "ujuno")), msg).unwrap();
+
+            let msg = ExecuteMsg::ApproveRelease { escrow_id, comment: None };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+            let paid: Vec<u128> = res
+                .messages
+                .into_iter()
+                .map(|sub| match sub.msg {
+                    CosmosMsg::Bank(BankMsg::Send { amount, .. }) => amount[0].amount.u128(),
+                    msg => panic!("unexpected message {:?}", msg),
+                })
+                .collect();
+            assert_eq!(paid, expected);
+            assert_eq!(paid.iter().sum::<u128>(), 100);
+        }
+
+        // Every beneficiary's share is rounded down by less than a unit, so the largest
+        // remainders never hand out more than one unit each
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("alice".to_string(), 1), ("bob".to_string(), 1), ("carol".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            remainder_policy: Some(RemainderPolicy::ProRataLargest),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(2, "ujuno")), msg).unwrap();
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 5, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        let recipients: Vec<CosmosMsg> = res.messages.into_iter().map(|sub| sub.msg).collect();
+        assert_eq!(
+            recipients,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send { to_address: "alice".to_string(), amount: coins(1, "ujuno") }),
+                CosmosMsg::Bank(BankMsg::Send { to_address: "bob".to_string(), amount: coins(1, "ujuno") }),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_invariants() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        let check = |deps: Deps| -> InvariantReport {
+            from_json(query(deps, mock_env(), QueryMsg::CheckInvariants { escrow_id: 1 }).unwrap()).unwrap()
+        };
+        assert!(check(deps.as_ref()).violations.is_empty());
+
+        // Corrupt the stored escrow the way a faulty migration could
+        let approval = |approver: &str| Approval { approver: Addr::unchecked(approver), approved_at: 0, comment: None };
+        let mut escrow = ESCROWS.load(deps.as_ref().storage, 1).unwrap();
+        escrow.approvals =
+            vec![approval("approver1"), approval("stranger"), approval("approver1"), approval("approver2")];
+        escrow.completed_at = Some(100);
+        ESCROWS.save(deps.as_mut().storage, 1, &escrow).unwrap();
+
+        assert_eq!(
+            check(deps.as_ref()).violations,
+            vec![
+                "approval by stranger who is not an approver".to_string(),
+                "duplicate approval by approver1".to_string(),
+                "4 approvals for 3 approvers".to_string(),
+                "open escrow has a completion time".to_string(),
+            ]
+        );
+
+        escrow.approvals.truncate(1);
+        escrow.status = EscrowStatus::Released;
+        escrow.completed_at = None;
+        ESCROWS.save(deps.as_mut().storage, 1, &escrow).unwrap();
+        assert_eq!(check(deps.as_ref()).violations, vec!["Released escrow has no completion time".to_string()]);
+    }
+
+    #[test]
+    fn beneficiary_change() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("alice".to_string(), 1)],
+            approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+
+        let propose = |new_beneficiary: &str| ExecuteMsg::ProposeBeneficiaryChange {
+            escrow_id: 1,
+            old_beneficiary: None,
+            new_beneficiary: new_beneficiary.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), propose("bob")).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose("alice")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidBeneficiary {}));
+
+        // A withdrawn proposal makes room for a new one
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose("mallory")).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose("bob")).unwrap_err();
+        assert!(matches!(err, ContractError::BeneficiaryChangePending {}));
+        let msg = ExecuteMsg::CancelBeneficiaryChange { escrow_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose("bob")).unwrap();
+
+        let approve_change = ExecuteMsg::ApproveBeneficiaryChange { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), approve_change.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NotAnApprover {}));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), approve_change.clone()).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("applied", "false")));
+
+        // Enough release approvals, but the payout waits for the pending change
+        for approver in ["approver1", "approver2"] {
+            let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+            let res = execute(deps.as_mut(), mock_env(), mock_info(approver, &[]), msg).unwrap();
+            assert!(res.messages.is_empty());
+        }
+        let release = ExecuteMsg::Release { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), release.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::BeneficiaryChangePending {}));
+
+        // The second approval meets the release threshold and applies the change
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), approve_change).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("applied", "true")));
+        let storage = deps.as_ref().storage;
+        assert!(!ESCROWS_BY_BENEFICIARY.has(storage, (&Addr::unchecked("alice"), 1)));
+        assert!(ESCROWS_BY_BENEFICIARY.has(storage, (&Addr::unchecked("bob"), 1)));
+        let escrow = ESCROWS.load(storage, 1).unwrap();
+        assert_eq!(escrow.beneficiaries, vec![(Addr::unchecked("bob"), 1)]);
+        assert_eq!(escrow.beneficiary_change, None);
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), release).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send { to_address: "bob".to_string(), amount: coins(1000, "ujuno") })
+        );
+    }
+
+    #[test]
+    fn escrow_dependency() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+        let create = |beneficiary: &str, depends_on: Option<u64>| {
+            ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![(beneficiary.to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                depends_on,
+                ..Default::default()
+            })
+        };
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+
+        // Only existing, and so earlier, escrows can be depended on
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), create("stage2", Some(1))).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowNotFound {}));
+        execute(deps.as_mut(), mock_env(), info.clone(), create("stage1", None)).unwrap();
+        execute(deps.as_mut(), mock_env(), info, create("stage2", Some(1))).unwrap();
+
+        // The dependent escrow collects its approval but holds on to the funds
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+        let release = ExecuteMsg::Release { escrow_id: 2 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), release.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::DependencyNotReleased {}));
+
+        // Once the first stage is released, so can the second
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), release).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send { to_address: "stage2".to_string(), amount: coins(1000, "ujuno") })
+        );
+    }
+
+    #[test]
+    fn fee_rounding_modes() {
+        let mut deps = mock_funded_dependencies();
+        let msg =
+            InstantiateMsg { fee_recipient: Some("treasury".to_string()), fee_bps: Some(100), ..Default::default() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateFeeRounding { rounding: FeeRounding::Ceil };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // A 1% fee on 101, 150 and 250 comes to 1.01, 1.5 and 2.5
+        let cases = [
+            (FeeRounding::Floor, [1, 1, 2]),
+            (FeeRounding::Ceil, [2, 2, 3]),
+            (FeeRounding::HalfEven, [1, 2, 2]),
+        ];
+        let mut escrow_id = 0;
+        for (rounding, fees) in cases {
+            let msg = ExecuteMsg::UpdateFeeRounding { rounding };
+            execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+            for (amount, expected_fee) in [101u128, 150, 250].into_iter().zip(fees) {
+                let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                    beneficiaries: vec![("beneficiary".to_string(), 1)],
+                    approvers: vec!["approver1".to_string()],
+                    description: "Test escrow".to_string(),
+                    ..Default::default()
+                });
+                execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(amount, "ujuno")), msg).unwrap();
+                escrow_id += 1;
+
+                let msg = ExecuteMsg::ApproveRelease { escrow_id, comment: None };
+                let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+                let sends: Vec<CosmosMsg> = res.messages.into_iter().map(|sub| sub.msg).collect();
+                assert_eq!(
+                    sends,
+                    vec![
+                        CosmosMsg::Bank(BankMsg::Send {
+                            to_address: "beneficiary".to_string(),
+                            amount: coins(amount - expected_fee, "ujuno"),
+                        }),
+                    ]
+                );
+                assert!(res.attributes.contains(&Attribute::new("fee", format!("{}ujuno", expected_fee))));
+            }
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        assert_eq!(from_json::<ConfigResponse>(&res).unwrap().fee_rounding, FeeRounding::HalfEven);
+    }
+
+    #[test]
+    fn can_cancel() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        for _ in 0..3 {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string(), "approver2".to_string()],
+                description: "Test escrow".to_string(),
+                threshold: Some(2),
+                arbiter: Some("arbiter".to_string()),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let can_cancel = |deps: Deps, escrow_id: u64, address: &str| -> CanCancelResponse {
+            let msg = QueryMsg::CanCancel { escrow_id, address: address.to_string() };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        let res = can_cancel(deps.as_ref(), 1, "creator");
+        assert!(res.can_cancel);
+        assert_eq!(res.reason, None);
+
+        let res = can_cancel(deps.as_ref(), 1, "approver1");
+        assert!(!res.can_cancel);
+        assert_eq!(res.reason.as_deref(), Some("only the creator can cancel"));
+
+        // An approval hands the decision over to the approvers
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        let res = can_cancel(deps.as_ref(), 1, "creator");
+        assert!(!res.can_cancel);
+        assert_eq!(res.reason.as_deref(), Some("escrow has approvals, approvers must vote to cancel"));
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("beneficiary", &[]), msg).unwrap();
+        let res = can_cancel(deps.as_ref(), 2, "creator");
+        assert!(!res.can_cancel);
+        assert_eq!(res.reason.as_deref(), Some("escrow is disputed"));
+
+        assert!(can_cancel(deps.as_ref(), 3, "creator").can_cancel);
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 3 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res = can_cancel(deps.as_ref(), 3, "creator");
+        assert!(!res.can_cancel);
+        assert_eq!(res.reason.as_deref(), Some("escrow is completed"));
+
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::CanCancel { escrow_id: 9, address: "creator".to_string() });
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn expected_amount() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let create = |funds: &[Coin], expected_amount: Option<Coin>| {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                expected_amount,
+                ..Default::default()
+            });
+            (mock_info("creator", funds), msg)
+        };
+
+        // Short, excess, wrong denom and extra coins are all rejected
+        for (funds, expected) in [
+            (coins(990, "ujuno"), coin(1000, "ujuno")),
+            (coins(1010, "ujuno"), coin(1000, "ujuno")),
+            (coins(1000, "uatom"), coin(1000, "ujuno")),
+            (vec![coin(1000, "ujuno"), coin(5, "uatom")], coin(1000, "ujuno")),
+        ] {
+            let (info, msg) = create(&funds, Some(expected));
+            let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+            assert!(matches!(err, ContractError::AmountMismatch {}));
+        }
+
+        let (info, msg) = create(&coins(1000, "ujuno"), Some(coin(1000, "ujuno")));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(res).unwrap();
+        assert_eq!(escrow.amount, coins(1000, "ujuno"));
+
+        // Without an expectation any amount is accepted
+        let (info, msg) = create(&coins(990, "ujuno"), None);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // An unfunded escrow checks the expectation once it is funded
+        let msg = ExecuteMsg::CreateUnfundedEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            expected_amount: Some(coin(1000, "ujuno")),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::FundEscrow { escrow_id: 3 };
+        let info = mock_info("creator", &coins(990, "ujuno"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::AmountMismatch {}));
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+    }
+
+    #[test]
+    fn expiring_escrows() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let now = mock_env().block.time.seconds();
+        for expires_at in [Some(now + 500), None, Some(now + 50), Some(now + 100), Some(now + 101), Some(now + 10)] {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                expires_at,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // Escrow 6 is released and no longer counts
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 6, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+
+        // Queries `elapsed` seconds after creation
+        let expiring = |deps: Deps, elapsed: u64, within_secs: u64, start_after: Option<u64>, limit: Option<u32>| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(elapsed);
+            let msg = QueryMsg::GetExpiringEscrows { within_secs, start_after, limit };
+            let res: EscrowListResponse = from_json(query(deps, env, msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|escrow| escrow.id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(expiring(deps.as_ref(), 0, 100, None, None), vec![3, 4]);
+        assert_eq!(expiring(deps.as_ref(), 0, 1000, None, None), vec![1, 3, 4, 5]);
+        assert_eq!(expiring(deps.as_ref(), 0, 1000, None, Some(2)), vec![1, 3]);
+        assert_eq!(expiring(deps.as_ref(), 0, 1000, Some(3), Some(2)), vec![4, 5]);
+        assert!(expiring(deps.as_ref(), 0, 10, None, None).is_empty());
+
+        // Escrows already past their expiration are left out
+        assert_eq!(expiring(deps.as_ref(), 100, 0, None, None), vec![4]);
+        assert_eq!(expiring(deps.as_ref(), 100, 400, None, None), vec![1, 4, 5]);
+    }
+
+    #[test]
+    fn auto_approve_creator() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        // The creator is one of three approvers in both escrows, the default majority asks for two
+        for auto_approve_creator in [None, Some(true)] {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["creator".to_string(), "approver1".to_string(), "approver2".to_string()],
+                description: "Test escrow".to_string(),
+                auto_approve_creator,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 2 }).unwrap();
+        let escrow: EscrowResponse = from_json(res).unwrap();
+        assert!(escrow.auto_approve_creator);
+        assert_eq!(escrow.approvals.len(), 1);
+        assert_eq!(escrow.approvals[0].approver, "creator");
+
+        // Without the flag the creator cannot approve and both other approvers are needed
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::CannotSelfApprove {}));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver2", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // With it the creator's approval counts and one more approval releases
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 2, comment: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyApproved {}));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CheckInvariants { escrow_id: 2 }).unwrap();
+        let report: InvariantReport = from_json(res).unwrap();
+        assert!(report.violations.is_empty());
+
+        // The creator's own approval does not stop them from cancelling
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["creator".to_string(), "approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            auto_approve_creator: Some(true),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 3 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // An escrow the creator's approval alone would release is rejected
+        let info = mock_info("creator", &coins(1000, "ujuno"));
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["creator".to_string(), "approver1".to_string()],
+            description: "Test escrow".to_string(),
+            threshold: Some(1),
+            auto_approve_creator: Some(true),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidThreshold {}));
+    }
+
+    #[test]
+    fn index_writes_do_not_scale() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let stored_bytes = |storage: &MockStorage| -> usize {
+            cosmwasm_std::Storage::range(storage, None, None, cosmwasm_std::Order::Ascending)
+                .map(|(key, value)| key.len() + value.len())
+                .sum()
+        };
+
+        // The same parties create escrow after escrow, each creation adds the same amount of data
+        let mut added = vec![];
+        for _ in 0..40 {
+            let before = stored_bytes(&deps.storage);
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string(), "approver2".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            added.push(stored_bytes(&deps.storage) - before);
+        }
+        // Skip the first escrows, whose running totals are still gaining digits
+        assert!(added[10..].iter().all(|bytes| *bytes == added[10]), "{:?}", added);
+
+        // Each index entry is a key of its own with an empty value
+        let creator = Addr::unchecked("creator");
+        let entries = ESCROWS_BY_CREATOR
+            .prefix(&creator)
+            .keys(&deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .count();
+        assert_eq!(entries, 40);
+    }
+
+    #[test]
+    fn pending_approvals_sorted() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        // Escrows needing 4, 2 and 3 approvals, of which 1, 1 and 2 are given by others
+        let approvers: Vec<String> = ["approver", "other1", "other2", "other3", "other4"]
+            .iter()
+            .map(|approver| approver.to_string())
+            .collect();
+        for (threshold, approved) in [(4, 1), (2, 1), (3, 2)] {
+            let info = mock_info("creator", &coins(1000, "ujuno"));
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: approvers.clone(),
+                description: "Test escrow".to_string(),
+                threshold: Some(threshold),
+                ..Default::default()
+            });
+            let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let escrow_id: u64 = res.attributes[1].value.parse().unwrap();
+            for other in &approvers[1..=approved] {
+                let msg = ExecuteMsg::ApproveRelease { escrow_id, comment: None };
+                execute(deps.as_mut(), mock_env(), mock_info(other, &[]), msg).unwrap();
+            }
+        }
+
+        let pending = |start_after: Option<u64>, limit: Option<u32>, sort: Option<SortKey>| -> Vec<u64> {
+            let msg = QueryMsg::GetPendingApprovals { approver: "approver".to_string(), start_after, limit, sort };
+            let res: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.escrows.into_iter().map(|e| e.id).collect()
+        };
+
+        assert_eq!(pending(None, None, None), vec![1, 2, 3]);
+        assert_eq!(pending(None, None, Some(SortKey::Oldest)), vec![1, 2, 3]);
+        assert_eq!(pending(Some(1), None, Some(SortKey::Oldest)), vec![2, 3]);
+        assert_eq!(pending(None, None, Some(SortKey::Newest)), vec![3, 2, 1]);
+        assert_eq!(pending(Some(3), Some(1), Some(SortKey::Newest)), vec![2]);
+        // Escrows 2 and 3 both miss one approval, the tie goes to the lower ID
+        assert_eq!(pending(None, None, Some(SortKey::NearestToRelease)), vec![2, 3, 1]);
+        assert_eq!(pending(None, Some(1), Some(SortKey::NearestToRelease)), vec![2]);
+
+        // The ranking has no cursor to page with
+        let msg = QueryMsg::GetPendingApprovals {
+            approver: "approver".to_string(),
+            start_after: Some(1),
+            limit: None,
+            sort: Some(SortKey::NearestToRelease),
+        };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+
+        // Escrow 4 misses two of three approvals but only one unit of weight, which ranks it
+        // with escrows 2 and 3
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: approvers.clone(),
+            description: "Test escrow".to_string(),
+            approver_weights: Some(vec![1, 3, 1, 1, 1]),
+            required_weight: Some(4),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 4, comment: None };
+        execute(deps.as_mut(), mock_env(), mock_info("other1", &[]), msg).unwrap();
+        let msg = QueryMsg::GetPendingApprovals {
+            approver: "approver".to_string(),
+            start_after: None,
+            limit: None,
+            sort: Some(SortKey::NearestToRelease),
+        };
+        let res: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.escrows.into_iter().map(|e| e.id).collect::<Vec<_>>(), vec![2, 3, 4, 1]);
+
+        // Only the 50 oldest pending escrows are ranked, so escrow 55 is left out even though
+        // it misses less than the escrows 5 to 54 before it
+        for threshold in [2; 50].into_iter().chain([1]) {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: approvers[..3].to_vec(),
+                description: "Test escrow".to_string(),
+                threshold: Some(threshold),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "uusdc")), msg).unwrap();
+        }
+        let res: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let ids: Vec<u64> = res.escrows.into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+        let msg = QueryMsg::GetPendingApprovals {
+            approver: "approver".to_string(),
+            start_after: None,
+            limit: Some(100),
+            sort: Some(SortKey::NearestToRelease),
+        };
+        let res: EscrowListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.escrows.len(), 50);
+        assert!(res.escrows.iter().all(|escrow| escrow.id <= 50));
+    }
+
+    #[test]
+    fn fee_accrual_and_withdrawal() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg {
+            fee_recipient: Some("treasury".to_string()),
+            fee_bps: Some(100),
+            cancel_fee_bps: Some(200),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let accrued = |deps: Deps| -> Vec<Coin> {
+            let res = query(deps, mock_env(), QueryMsg::GetAccruedFees {}).unwrap();
+            from_json::<AccruedFeesResponse>(&res).unwrap().fees
+        };
+        assert!(accrued(deps.as_ref()).is_empty());
+
+        for funds in [coins(1000, "ujuno"), coins(2000, "ujuno"), coins(500, "uusdc"), coins(1000, "ujuno")] {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &funds), msg).unwrap();
+        }
+
+        // Releases and cancellations only pay out the beneficiaries and the creator
+        for escrow_id in 1..=3 {
+            let msg = ExecuteMsg::ApproveRelease { escrow_id, comment: None };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+            assert_eq!(res.messages.len(), 1);
+        }
+        let msg = ExecuteMsg::CancelEscrow { escrow_id: 4 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(accrued(deps.as_ref()), vec![coin(50, "ujuno"), coin(5, "uusdc")]);
+
+        // The accrued fees are set aside from the open escrows' funds
+        let msg = QueryMsg::GetReconciliation { denom: "ujuno".to_string() };
+        let res: ReconciliationResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.accrued_fees, Uint128::new(50));
+
+        // Only the fee recipient and the admin can withdraw
+        let msg = ExecuteMsg::WithdrawFees { denom: "ujuno".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("treasury", &[]), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send { to_address: "treasury".to_string(), amount: coins(50, "ujuno") })
+        );
+        assert_eq!(accrued(deps.as_ref()), coins(5, "uusdc"));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NoFeesAccrued {}));
+
+        let msg = ExecuteMsg::WithdrawFees { denom: "uusdc".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send { to_address: "treasury".to_string(), amount: coins(5, "uusdc") })
+        );
+        assert!(accrued(deps.as_ref()).is_empty());
+    }
+
+    #[test]
+    fn replace_auto_approved_creator() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["creator".to_string(), "approver1".to_string(), "approver2".to_string()],
+            description: "Test escrow".to_string(),
+            auto_approve_creator: Some(true),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+
+        // The creator's approval leaves with them, so two approvals are still needed
+        let msg = ExecuteMsg::ReplaceApprover {
+            escrow_id: 1,
+            old: "creator".to_string(),
+            new: "approver3".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetEscrow { escrow_id: 1 }).unwrap();
+        let escrow: EscrowResponse = from_json(res).unwrap();
+        assert!(escrow.approvals.is_empty());
+        assert!(!escrow.auto_approve_creator);
+
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 1, comment: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver3", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn beneficiary_change_disputes_and_weights() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+        for (arbiter, approver_weights, required_weight) in
+            [(Some("arbiter"), None, None), (Some("arbiter"), None, None), (None, Some(vec![3, 1, 1]), Some(3))]
+        {
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("alice".to_string(), 1)],
+                approvers: vec!["approver1".to_string(), "approver2".to_string(), "approver3".to_string()],
+                description: "Test escrow".to_string(),
+                arbiter: arbiter.map(String::from),
+                approver_weights,
+                required_weight,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        }
+        let propose = |escrow_id: u64| ExecuteMsg::ProposeBeneficiaryChange {
+            escrow_id,
+            old_beneficiary: None,
+            new_beneficiary: "bob".to_string(),
+        };
+
+        // No proposals while the arbiter decides
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose(1)).unwrap_err();
+        assert!(matches!(err, ContractError::Disputed {}));
+
+        // A change proposed before the dispute can no longer be approved, nor does it hold
+        // back the arbiter's award
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose(2)).unwrap();
+        let msg = ExecuteMsg::RaiseDispute { escrow_id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ApproveBeneficiaryChange { escrow_id: 2 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Disputed {}));
+        let msg = ExecuteMsg::ResolveDispute { escrow_id: 2, award_to_beneficiary: true };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("arbiter", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send { to_address: "alice".to_string(), amount: coins(1000, "ujuno") })
+        );
+        let msg = ExecuteMsg::CancelBeneficiaryChange { escrow_id: 2 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowCompleted {}));
+
+        // Withdrawing a proposal is recorded
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose(3)).unwrap();
+        let msg = ExecuteMsg::CancelBeneficiaryChange { escrow_id: 3 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = QueryMsg::GetEscrowHistory { escrow_id: 3, start_after: None, limit: None };
+        let history: EscrowHistoryResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(history.events.last().unwrap().kind, EscrowEventKind::BeneficiaryChangeCancelled);
+
+        // The heaviest approver alone carries the release weight, and so the change
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose(3)).unwrap();
+        let msg = ExecuteMsg::ApproveBeneficiaryChange { escrow_id: 3 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("applied", "true")));
+
+        // An auto-approved creator backs their own proposal
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("alice".to_string(), 1)],
+            approvers: vec!["creator".to_string(), "approver1".to_string()],
+            description: "Test escrow".to_string(),
+            auto_approve_creator: Some(true),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), propose(4)).unwrap();
+        let msg = ExecuteMsg::ApproveBeneficiaryChange { escrow_id: 4 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("applied", "true")));
+    }
+
+    #[test]
+    fn failed_batch_approval_leaves_no_history() {
+        let mut deps = mock_funded_dependencies();
+        let msg = InstantiateMsg { require_acceptance: Some(true), ..Default::default() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+
+        // The approval would release the escrow, which the beneficiary has not accepted yet
+        let msg = ExecuteMsg::ApproveReleaseBatch { escrow_ids: vec![1] };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("skipped", "1")));
+
+        let escrow = ESCROWS.load(deps.as_ref().storage, 1).unwrap();
+        assert!(escrow.approvals.is_empty());
+        let msg = QueryMsg::GetEscrowHistory { escrow_id: 1, start_after: None, limit: None };
+        let history: EscrowHistoryResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let kinds: Vec<EscrowEventKind> = history.events.into_iter().map(|event| event.kind).collect();
+        assert_eq!(kinds, vec![EscrowEventKind::Created]);
+    }
+
+    #[test]
+    fn migrate_v2_config() {
+        // v0.2.0 builds stored no config at first, then one without the later fields
+        let configs: [Option<&[u8]>; 3] = [
+            None,
+            Some(br#"{"admin":"owner","paused":false,"fee_recipient":null,"fee_bps":0}"#),
+            Some(br#"{"admin":"owner","paused":false,"fee_recipient":null,"fee_bps":0,"require_acceptance":true}"#),
+        ];
+        for config in configs {
+            let mut deps = mock_funded_dependencies();
+            deps.querier.update_wasm(|query| match query {
+                WasmQuery::ContractInfo { .. } => SystemResult::Ok(ContractResult::Ok(Binary::from(
+                    br#"{"code_id":1,"creator":"deployer","admin":"owner","pinned":false}"#.as_slice(),
+                ))),
+                _ => SystemResult::Err(SystemError::Unknown {}),
+            });
+            cw2::set_contract_version(deps.as_mut().storage, "crates.io:cosmoscrow", "0.2.0").unwrap();
+            ESCROW_COUNTER.save(deps.as_mut().storage, &0).unwrap();
+            if let Some(config) = config {
+                cosmwasm_std::Storage::set(deps.as_mut().storage, b"config", config);
+            }
+
+            migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+            let migrated: ConfigResponse = from_json(res).unwrap();
+            assert_eq!(migrated.admin, Addr::unchecked("owner"));
+            assert_eq!(migrated.max_description_length, 500);
+            assert_eq!(migrated.require_acceptance, config.is_some_and(|config| config.ends_with(b"true}")));
+
+            let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+                beneficiaries: vec![("beneficiary".to_string(), 1)],
+                approvers: vec!["approver1".to_string()],
+                description: "Test escrow".to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        }
+    }
+
+    #[test]
+    fn would_release_matches_approve() {
+        let mut deps = mock_funded_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), InstantiateMsg::default()).unwrap();
+
+        let create = CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["approver1".to_string()],
+            description: "Test escrow".to_string(),
+            ..Default::default()
+        };
+        let msg = ExecuteMsg::CreateUnfundedEscrow(create.clone());
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg { depends_on: Some(1), ..create.clone() });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        let msg = ExecuteMsg::CreateEscrow(create);
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+        let msg = ExecuteMsg::ProposeBeneficiaryChange {
+            escrow_id: 3,
+            old_beneficiary: None,
+            new_beneficiary: "beneficiary2".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // The creator's approval goes stale after 100 seconds
+        let msg = ExecuteMsg::CreateEscrow(CreateEscrowMsg {
+            beneficiaries: vec![("beneficiary".to_string(), 1)],
+            approvers: vec!["creator".to_string(), "approver1".to_string()],
+            description: "Test escrow".to_string(),
+            threshold: Some(2),
+            approval_ttl: Some(100),
+            auto_approve_creator: Some(true),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(1000, "ujuno")), msg).unwrap();
+
+        let at = |elapsed: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(elapsed);
+            env
+        };
+        let would_release = |deps: Deps, escrow_id: u64, approver: &str, elapsed: u64| -> bool {
+            let msg = QueryMsg::WouldRelease { escrow_id, approver: approver.to_string() };
+            from_json(query(deps, at(elapsed), msg).unwrap()).unwrap()
+        };
+        let can_approve = |deps: Deps, escrow_id: u64, address: &str, elapsed: u64| -> bool {
+            let msg = QueryMsg::GetRole { escrow_id, address: address.to_string() };
+            let role: RoleResponse = from_json(query(deps, at(elapsed), msg).unwrap()).unwrap();
+            role.can_approve
+        };
+
+        // An unfunded escrow cannot be approved at all
+        assert!(!can_approve(deps.as_ref(), 1, "approver1", 0));
+        assert!(!would_release(deps.as_ref(), 1, "approver1", 0));
+
+        // A pending dependency or beneficiary change keeps the approval without releasing
+        for escrow_id in [2, 3] {
+            assert!(can_approve(deps.as_ref(), escrow_id, "approver1", 0));
+            assert!(!would_release(deps.as_ref(), escrow_id, "approver1", 0));
+            let msg = ExecuteMsg::ApproveRelease { escrow_id, comment: None };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("approver1", &[]), msg).unwrap();
+            assert!(res.messages.is_empty());
+        }
+
+        // Once their approval went stale, an auto-approved creator renewing it releases the escrow
+        let msg = ExecuteMsg::ApproveRelease { escrow_id: 4, comment: None };
+        execute(deps.as_mut(), at(200), mock_info("approver1", &[]), msg.clone()).unwrap();
+        assert!(can_approve(deps.as_ref(), 4, "creator", 200));
+        assert!(would_release(deps.as_ref(), 4, "creator", 200));
+        let res = execute(deps.as_mut(), at(200), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
 }
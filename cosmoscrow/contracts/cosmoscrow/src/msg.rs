@@ -1,34 +1,444 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Coin};
+use cosmwasm_std::{Addr, Binary, Coin, Int256, Order, Uint128};
+
+use crate::state::{BeneficiaryChange, ApprovalMode, ApprovalPolicy, ApprovalTier, Approval, EscrowEventKind, EscrowStatus, FeeRounding, RateLimit, RemainderPolicy, Settlement};
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+#[derive(Default)]
+pub struct InstantiateMsg {
+    /// Admin address, defaults to the instantiator
+    pub admin: Option<String>,
+    /// Address receiving the platform fee, required when `fee_bps` is non-zero
+    pub fee_recipient: Option<String>,
+    /// Platform fee taken on release, in basis points (defaults to no fee)
+    pub fee_bps: Option<u16>,
+    /// Fee taken from the refund when a creator cancels an escrow, in basis points
+    /// (defaults to no fee). Only charged when a fee recipient is set.
+    pub cancel_fee_bps: Option<u16>,
+    /// Require the beneficiaries to accept an escrow before it can release (defaults to false)
+    pub require_acceptance: Option<bool>,
+    /// Maximum escrow description length in characters (defaults to 500)
+    pub max_description_length: Option<u32>,
+    /// Minimum escrow amount per denom; denoms not listed have no minimum
+    pub min_amounts: Option<Vec<Coin>>,
+    /// Denoms escrows can be created with (defaults to allowing every denom)
+    pub allowed_denoms: Option<Vec<String>>,
+    /// Reject escrows listing a beneficiary as an approver (defaults to false)
+    pub block_beneficiary_approver: Option<bool>,
+    /// Require `FinalizeRelease` after the approval threshold is reached instead of
+    /// releasing right away (defaults to false)
+    pub two_phase_release: Option<bool>,
+    /// Seconds a two-phase release waits after the approval threshold is reached before it
+    /// can be finalized, leaving time to raise a dispute (defaults to 0)
+    pub grace_period: Option<u64>,
+    /// Addresses allowed to create escrows. When set, nobody else can create escrows
+    /// (defaults to everyone being allowed).
+    pub creator_allowlist: Option<Vec<String>>,
+    /// Limit on how many escrows one address can create per time window (defaults to no limit)
+    pub rate_limit: Option<RateLimit>,
+    /// Threshold given to escrows created without `threshold`, `mode`, `policy` or
+    /// `required_weight` (defaults to a simple majority)
+    pub default_threshold_policy: Option<ApprovalMode>,
+    /// Stricter approval requirements by escrowed amount, e.g. unanimous approval for
+    /// escrows of 1M ujuno or more. The requirement is fixed when the escrow is funded and
+    /// raised again by top-ups. Escrows with a `required_weight` are not tiered.
+    pub approval_tiers: Option<Vec<ApprovalTier>>,
+    /// How fractional fees are rounded (defaults to rounding down)
+    pub fee_rounding: Option<FeeRounding>,
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    /// Create a new escrow with the sent funds
-    CreateEscrow {
-        /// Address that will receive the funds when released
-        beneficiary: String,
-        /// First approver address (usually the creator)
-        approver1: String,
-        /// Second approver address
-        approver2: String,
-        /// Optional third party approver address
-        approver3: Option<String>,
-        /// Description of the escrow conditions
-        description: String,
+    /// Create a new escrow with the sent funds (one or more denominations)
+    CreateEscrow(CreateEscrowMsg),
+    /// Create an escrow without funds, for deals agreed before the funds are ready. It
+    /// cannot be approved until the creator funds it with `FundEscrow`.
+    CreateUnfundedEscrow(CreateEscrowMsg),
+    /// Attach the sent funds to an unfunded escrow, which fixes its amount (only the
+    /// creator can do this)
+    FundEscrow {
+        /// ID of the escrow to fund
+        escrow_id: u64,
     },
     /// Approve the release of funds for a specific escrow
     ApproveRelease {
         /// ID of the escrow to approve
         escrow_id: u64,
+        /// Optional reason for approving, stored with the approval (at most 280 characters)
+        comment: Option<String>,
+    },
+    /// Submit an approval signed off-chain by `approver`, who needs a key registered with
+    /// `SetApprovalKey`. The signature is over the SHA-256 hash of `approval_sign_bytes`, and
+    /// can be submitted by anyone until the approver next approves or revokes the escrow.
+    ApproveReleaseSigned {
+        escrow_id: u64,
+        approver: String,
+        /// 64 byte secp256k1 signature
+        signature: Binary,
+    },
+    /// Register the secp256k1 public key the sender signs approvals with, or remove it
+    SetApprovalKey {
+        pubkey: Option<Binary>,
+    },
+    /// Approve the release of several escrows at once (at most 30). Escrows that cannot be
+    /// approved are skipped and listed in the `skipped` attribute.
+    ApproveReleaseBatch {
+        /// IDs of the escrows to approve
+        escrow_ids: Vec<u64>,
+    },
+    /// Let another address approve the release on the sender's behalf (only approvers can
+    /// do this). The delegate can only approve; it replaces any earlier delegate.
+    SetApprovalDelegate {
+        /// ID of the escrow to delegate approval for
+        escrow_id: u64,
+        /// Address approving on the sender's behalf
+        delegate: String,
+    },
+    /// Remove the sender's approval delegate
+    RevokeApprovalDelegate {
+        /// ID of the escrow to remove the delegate from
+        escrow_id: u64,
+    },
+    /// Withdraw a release approval given earlier, as long as the escrow is still open
+    RevokeApproval {
+        /// ID of the escrow to withdraw the approval from
+        escrow_id: u64,
+    },
+    /// Release a fully approved escrow whose `release_after` time has passed (anyone can do
+    /// this, unless two-phase release is enabled; it then works like `FinalizeRelease`)
+    Release {
+        /// ID of the escrow to release
+        escrow_id: u64,
+    },
+    /// Pay out a fully approved escrow when two-phase release is enabled (only the creator
+    /// or an approver can do this). With a grace period, anyone can do this once the grace
+    /// period has passed, and nobody before.
+    FinalizeRelease {
+        /// ID of the escrow to release
+        escrow_id: u64,
     },
     /// Cancel an escrow (only creator can do this if no approvals yet)
     CancelEscrow {
         /// ID of the escrow to cancel
         escrow_id: u64,
     },
+    /// Cancel the sender's open escrows that have no approvals yet, oldest first, going
+    /// through up to `limit` of the sender's escrows after `start_after` (defaults to 10, at
+    /// most 30). Escrows that cannot be cancelled this way are left open and listed as
+    /// `skipped`. Pass the returned `last_checked` as `start_after` to continue until it is
+    /// empty.
+    CancelAllMyEscrows {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Vote to cancel an escrow and refund the creator. Once as many approvers as required
+    /// for a release have voted, the funds are returned even if some approvals were given.
+    ApproveCancel {
+        /// ID of the escrow to cancel
+        escrow_id: u64,
+    },
+    /// Refund an expired escrow to its creator (anyone can call this once it has expired)
+    RefundExpired {
+        /// ID of the escrow to refund
+        escrow_id: u64,
+    },
+    /// Refund every expired escrow to its creator, earliest expiration first, up to `limit` escrows
+    /// (defaults to 10, at most 30). Anyone can call this; repeat it until `swept` is 0.
+    /// Disputed escrows are left for their arbiter.
+    SweepExpired {
+        limit: Option<u32>,
+    },
+    /// Accept an escrow as one of its beneficiaries (only beneficiaries can do this). The
+    /// escrow counts as accepted once every beneficiary did.
+    AcceptEscrow {
+        /// ID of the escrow to accept
+        escrow_id: u64,
+    },
+    /// Have your share of an escrow sent to another address (only beneficiaries can do
+    /// this). Setting your own address sends it to you again.
+    SetPayoutAddress {
+        /// ID of the escrow
+        escrow_id: u64,
+        /// Address receiving your share on release
+        payout_address: String,
+    },
+    /// Refund an escrow whose beneficiaries did not all accept it by its `accept_deadline`
+    /// (only the creator can do this)
+    ReclaimUnaccepted {
+        /// ID of the escrow to reclaim
+        escrow_id: u64,
+    },
+    /// Flag a problem with an escrow, freezing it until the arbiter resolves the dispute
+    /// (only the creator or a beneficiary can do this, and only if the escrow has an arbiter)
+    RaiseDispute {
+        /// ID of the disputed escrow
+        escrow_id: u64,
+    },
+    /// Settle a disputed escrow (only the arbiter can do this)
+    ResolveDispute {
+        /// ID of the disputed escrow
+        escrow_id: u64,
+        /// Release the funds to the beneficiaries if true, refund the creator otherwise
+        award_to_beneficiary: bool,
+    },
+    /// Agree to cancel an escrow with part of the funds going to the beneficiaries
+    /// (only the creator or a beneficiary can do this). The escrow is settled once the
+    /// creator and every beneficiary agreed to the same split, or right away when called
+    /// by the arbiter.
+    SettleCancellation {
+        /// ID of the escrow to settle
+        escrow_id: u64,
+        /// Funds split between the beneficiaries by weight; the rest is refunded to the creator
+        to_beneficiary: Vec<Coin>,
+    },
+    /// Hand over the creator role of an open escrow, including cancel rights and refunds
+    /// (only the current creator can do this)
+    TransferCreator {
+        /// ID of the escrow to transfer
+        escrow_id: u64,
+        /// Address of the new creator
+        new_creator: String,
+    },
+    /// Swap an approver for another address, e.g. after a key compromise (only the creator
+    /// can do this, and only before anyone approved). The new approver takes over the old
+    /// one's weight.
+    ReplaceApprover {
+        /// ID of the escrow
+        escrow_id: u64,
+        /// Current approver to remove
+        old: String,
+        /// Address replacing it, which must not already be an approver
+        new: String,
+    },
+    /// Propose swapping a beneficiary for another address (only the creator can do this).
+    /// The change needs as many approvers as the release, and the escrow cannot be
+    /// released while it is pending.
+    ProposeBeneficiaryChange {
+        escrow_id: u64,
+        /// Beneficiary to replace, which can be left out when the escrow has only one
+        old_beneficiary: Option<String>,
+        /// Address taking over the beneficiary's share
+        new_beneficiary: String,
+    },
+    /// Agree to the pending beneficiary change (only approvers other than the creator can
+    /// do this). The change is applied once enough approvers agreed.
+    ApproveBeneficiaryChange { escrow_id: u64 },
+    /// Withdraw the pending beneficiary change (only the creator can do this)
+    CancelBeneficiaryChange { escrow_id: u64 },
+    /// Add the sent funds to an open escrow before anyone approved it (only the creator can
+    /// do this). Only denoms already held by the escrow can be added.
+    TopUp {
+        /// ID of the escrow to top up
+        escrow_id: u64,
+    },
+    /// Change the description of an open escrow before anyone approved it
+    /// (only the creator can do this)
+    UpdateDescription {
+        /// ID of the escrow to update
+        escrow_id: u64,
+        /// New description of the escrow conditions
+        description: String,
+    },
+    /// Save escrow settings under a name for later use with `CreateFromTemplate`,
+    /// replacing the sender's template of the same name
+    SaveTemplate {
+        name: String,
+        approvers: Vec<String>,
+        threshold: Option<u32>,
+        description: String,
+    },
+    /// Create an escrow with the sent funds from one of the sender's templates
+    CreateFromTemplate {
+        /// Name of the template
+        name: String,
+        /// Address receiving the funds on release
+        beneficiary: String,
+    },
+    /// Pause or unpause the creation of new escrows (admin only)
+    SetPaused {
+        paused: bool,
+    },
+    /// Restrict the denoms new escrows can be created with, or lift the restriction with
+    /// `None` (admin only)
+    UpdateAllowedDenoms {
+        allowed_denoms: Option<Vec<String>>,
+    },
+    /// Limit how many escrows one address can create per time window, or lift the limit
+    /// with `None` (admin only)
+    UpdateRateLimit {
+        rate_limit: Option<RateLimit>,
+    },
+    /// Set the threshold given to new escrows created without one, or go back to a simple
+    /// majority with `None` (admin only). Existing escrows keep their threshold.
+    UpdateDefaultThresholdPolicy {
+        policy: Option<ApprovalMode>,
+    },
+    /// Replace the approval tiers for high-value escrows (admin only). Existing escrows keep
+    /// their requirement.
+    UpdateApprovalTiers {
+        tiers: Vec<ApprovalTier>,
+    },
+    /// Set how fractional fees are rounded (admin only)
+    UpdateFeeRounding {
+        rounding: FeeRounding,
+    },
+    /// Add and remove addresses allowed to create escrows, and turn the allowlist on or off
+    /// (admin only). While it is off, everyone can create escrows.
+    UpdateCreatorAllowlist {
+        /// Turn the allowlist on or off, leaving it as is when omitted
+        enabled: Option<bool>,
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Set the display name of a denom shown in escrow responses, or remove it with `None`
+    /// (admin only)
+    SetDenomAlias {
+        denom: String,
+        alias: Option<String>,
+    },
+    /// Transfer the contract ownership (the admin role) in two steps
+    UpdateOwnership(OwnershipAction),
+    /// Propose sending the funds of a stuck escrow to `to` (owner only). Replaces any earlier
+    /// proposal for the escrow and can only be executed 7 days later.
+    ProposeEmergencyWithdraw {
+        /// ID of the escrow to withdraw
+        escrow_id: u64,
+        /// Address receiving the escrowed funds
+        to: String,
+    },
+    /// Withdraw the funds of an escrow once its proposal's timelock has passed (owner only).
+    /// The escrow is closed as cancelled.
+    ExecuteEmergencyWithdraw {
+        /// ID of the escrow to withdraw
+        escrow_id: u64,
+    },
+    /// Send the fees accrued in a denom to the fee recipient (fee recipient or admin only)
+    WithdrawFees {
+        denom: String,
+    },
+}
+
+#[cw_serde]
+pub enum OwnershipAction {
+    /// Propose a new owner, replacing any earlier proposal (owner only)
+    TransferOwnership {
+        new_owner: String,
+        /// Optional time (Unix seconds) after which the proposal can no longer be accepted
+        expiry: Option<u64>,
+    },
+    /// Accept a proposed transfer and become the owner (proposed owner only)
+    AcceptOwnership {},
+    /// Withdraw a proposed transfer (owner only)
+    CancelTransfer {},
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct CreateEscrowMsg {
+    /// Addresses that will receive the funds when released, each with a non-zero weight.
+    /// The released amount is split by weight; rounding leftovers are assigned following
+    /// `remainder_policy`.
+    pub beneficiaries: Vec<(String, u64)>,
+    /// Approver addresses (between 1 and 10, without duplicates unless
+    /// `allow_duplicate_approvers` is set). Beneficiaries may be among them.
+    pub approvers: Vec<String>,
+    /// Description of the escrow conditions
+    pub description: String,
+    /// Optional number of approvals required to release the funds. Must be between 1 and the
+    /// number of unique approvers other than the creator. Defaults to a simple majority.
+    pub threshold: Option<u32>,
+    /// Optional shorthand for the threshold, resolved when the escrow is created.
+    /// Cannot be combined with `threshold`.
+    pub mode: Option<ApprovalMode>,
+    /// Optional governance model the approvers must match, which then sets the threshold.
+    /// Cannot be combined with `threshold` or `mode`.
+    pub policy: Option<ApprovalPolicy>,
+    /// Optional number of seconds an approval counts towards the release. Older approvals
+    /// are ignored until the approver approves again.
+    pub approval_ttl: Option<u64>,
+    /// Optional non-zero weight of each approver, in the order of `approvers`. Approvers
+    /// count once each by default.
+    pub approver_weights: Option<Vec<u64>>,
+    /// Optional total weight of approvals required to release, instead of a number of
+    /// approvals. Must be reachable by the approvers other than the creator and cannot be
+    /// combined with `threshold`, `mode` or `policy`.
+    pub required_weight: Option<u64>,
+    /// Optional expiration time (Unix seconds) after which the escrow can be refunded
+    pub expires_at: Option<u64>,
+    /// Optional time (Unix seconds) before which the funds are not released, even when
+    /// approved. Must lie before `expires_at`.
+    pub release_after: Option<u64>,
+    /// Optional neutral arbiter who settles disputes
+    pub arbiter: Option<String>,
+    /// Optional time (Unix seconds) by which every beneficiary must accept the escrow.
+    /// Once it passed without acceptance, the creator can reclaim the funds.
+    pub accept_deadline: Option<u64>,
+    /// Silently drop duplicate approvers instead of rejecting them (defaults to false)
+    pub allow_duplicate_approvers: Option<bool>,
+    /// Optional key making retries safe: if the creator already created an escrow with
+    /// this key, no new escrow is created, the sent funds are returned and the response
+    /// carries the existing escrow ID
+    pub idempotency_key: Option<String>,
+    /// Optional opaque data such as a JSON object with an invoice ID (at most 2KB). It is
+    /// stored and returned as is.
+    pub metadata: Option<Binary>,
+    /// Optional address recorded as the creator instead of the sender, e.g. when a custody
+    /// service funds the escrow. The sender keeps no rights over the escrow: the recorded
+    /// creator alone can cancel it and receives every refund.
+    pub creator: Option<String>,
+    /// Optional contract executed with `release_hook_msg` when the funds are released, after
+    /// the payouts. If it fails, the whole release is reverted. Both must be set together.
+    pub release_hook: Option<String>,
+    pub release_hook_msg: Option<Binary>,
+    /// Optional assignment of the rounding leftover of split payouts (defaults to the first
+    /// beneficiary)
+    pub remainder_policy: Option<RemainderPolicy>,
+    /// Optional ID of an existing escrow that must be released first. Approvals are
+    /// collected meanwhile, and the escrow is released through `Release` afterwards.
+    pub depends_on: Option<u64>,
+    /// Optional exact amount the escrow must receive. Creation fails if the sent funds differ,
+    /// guarding against tokens that arrive reduced. Unfunded escrows check it in `FundEscrow`.
+    pub expected_amount: Option<Coin>,
+    /// Count the creator, if listed as an approver, as approving right away (defaults to
+    /// false). Otherwise the creator's entry is ignored and the others must reach the threshold.
+    pub auto_approve_creator: Option<bool>,
+}
+
+/// Role an address holds on an escrow
+#[cw_serde]
+pub enum EscrowRole {
+    Creator,
+    Beneficiary,
+    Approver,
+}
+
+/// Order of a listing query's results
+#[cw_serde]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Ascending => Order::Ascending,
+            SortOrder::Descending => Order::Descending,
+        }
+    }
+}
+
+/// Order of the pending approvals
+#[cw_serde]
+pub enum SortKey {
+    /// Lowest escrow ID first
+    Oldest,
+    /// Highest escrow ID first
+    Newest,
+    /// Least approval weight still missing first, then lowest escrow ID. Only the approver's
+    /// 50 oldest pending escrows, found among their first 200 escrows, are ranked, and a
+    /// single page is returned.
+    NearestToRelease,
 }
 
 #[cw_serde]
@@ -37,18 +447,175 @@ pub enum QueryMsg {
     /// Get details of a specific escrow
     #[returns(EscrowResponse)]
     GetEscrow { escrow_id: u64 },
+
+    /// Get several escrows at once, in the order of `ids`. IDs without an escrow are
+    /// skipped. At most 30 IDs can be requested.
+    #[returns(EscrowListResponse)]
+    GetEscrows { ids: Vec<u64> },
+
+    /// Get the roles an address holds on an escrow and whether it can approve it now
+    #[returns(RoleResponse)]
+    GetRole { escrow_id: u64, address: String },
+
+    /// Get how many approvals an escrow has and still needs, and who signed
+    #[returns(ApprovalStatusResponse)]
+    GetApprovalStatus { escrow_id: u64 },
+
+    /// Get the approvals of an escrow with their times and comments, ordered by approver
+    /// address. `GetEscrow` returns them all at once; use this for escrows with many approvers.
+    #[returns(ApprovalListResponse)]
+    GetApprovals {
+        escrow_id: u64,
+        /// Approver of the last approval on the previous page
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Check whether `CancelEscrow` sent by `address` would currently succeed, and why not
+    #[returns(CanCancelResponse)]
+    CanCancel { escrow_id: u64, address: String },
+
+    /// Check whether an approval by `approver` right now would release the funds
+    #[returns(bool)]
+    WouldRelease { escrow_id: u64, approver: String },
+
+    /// Get the nonce `approver` has to sign for their next `ApproveReleaseSigned`
+    #[returns(u64)]
+    GetApprovalNonce { escrow_id: u64, approver: String },
     
-    /// Get all escrows for a specific address (as creator, beneficiary, or approver)
+    /// Get the escrow templates saved by an address, ordered by name
+    #[returns(TemplateListResponse)]
+    GetTemplates {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Get the contract configuration
+    #[returns(ConfigResponse)]
+    GetConfig {},
+
+    /// Get the contract version and which optional features this deployment has enabled
+    #[returns(CapabilitiesResponse)]
+    GetCapabilities {},
+
+    /// Get the contract owner and any pending ownership transfer
+    #[returns(OwnershipResponse)]
+    Ownership {},
+
+    /// Get escrow counts and the total value locked
+    #[returns(StatsResponse)]
+    GetStats {},
+
+    /// Count the escrows with the given status, or all escrows
+    #[returns(u64)]
+    CountEscrows { status: Option<EscrowStatus> },
+
+    /// Get the fees accrued for the fee recipient and not withdrawn yet
+    #[returns(AccruedFeesResponse)]
+    GetAccruedFees {},
+
+    /// Compare the funds locked in open escrows with the contract's balance of a denom
+    #[returns(ReconciliationResponse)]
+    GetReconciliation { denom: String },
+
+    /// Check the internal consistency of an escrow, e.g. after a migration. Consistent
+    /// escrows come back without violations.
+    #[returns(InvariantReport)]
+    CheckInvariants { escrow_id: u64 },
+
+    /// Get all escrows for a specific address (as creator, beneficiary, or approver),
+    /// completed ones included unless filtered out by `status`
     #[returns(EscrowListResponse)]
     GetEscrowsByAddress { 
         address: String,
+        /// Only return escrows with this status
+        status: Option<EscrowStatus>,
+        /// Only return escrows where the address holds this role (defaults to any role)
+        role: Option<EscrowRole>,
         start_after: Option<u64>,
         limit: Option<u32>,
     },
     
-    /// Get all escrows (paginated)
+    /// Get the open escrows an approver has not approved yet, leaving out escrows the
+    /// approver created since they can't approve those
+    #[returns(EscrowListResponse)]
+    GetPendingApprovals {
+        approver: String,
+        /// ID of the last escrow on the previous page. Not supported with `NearestToRelease`.
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        /// Order of the results (defaults to `Oldest`)
+        sort: Option<SortKey>,
+    },
+
+    /// Get the escrows holding a denom in any status, in ID order
+    #[returns(EscrowListResponse)]
+    GetEscrowsByDenom {
+        denom: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Get the open escrows created more than `older_than_secs` seconds ago that still wait
+    /// for approvals, in ID order
+    #[returns(EscrowListResponse)]
+    GetStaleEscrows {
+        older_than_secs: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Get the open escrows that expire within the next `within_secs` seconds and have not
+    /// expired yet, in ID order
+    #[returns(EscrowListResponse)]
+    GetExpiringEscrows {
+        within_secs: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Get the open escrows the address can act on in any of its roles, each with the
+    /// actions available to it. Escrows without any action are skipped.
+    #[returns(ActionableResponse)]
+    GetActionable {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Get all escrows (paginated). Pages are taken over escrow IDs, and escrows are never
+    /// removed once created, so a cursor stays valid when escrows are released or cancelled
+    /// between pages.
     #[returns(EscrowListResponse)]
     GetAllEscrows {
+        /// Only return escrows with this status
+        status: Option<EscrowStatus>,
+        /// Whether to return released, cancelled and expired escrows (defaults to true)
+        include_completed: Option<bool>,
+        /// ID of the last escrow on the previous page, in the requested order
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        /// Order by ID, which is also creation order (defaults to ascending). Use
+        /// descending to get the most recent escrows first.
+        order: Option<SortOrder>,
+    },
+
+    /// Get escrows created between two times (Unix seconds, both inclusive), oldest first
+    #[returns(EscrowListResponse)]
+    GetEscrowsByTimeRange {
+        start_time: u64,
+        end_time: u64,
+        /// `(created_at, id)` of the last escrow on the previous page
+        start_after: Option<(u64, u64)>,
+        limit: Option<u32>,
+    },
+
+    /// Get the recorded changes to an escrow, oldest first
+    #[returns(EscrowHistoryResponse)]
+    GetEscrowHistory {
+        escrow_id: u64,
+        /// Sequence number of the last event on the previous page
         start_after: Option<u64>,
         limit: Option<u32>,
     },
@@ -58,16 +625,211 @@ pub enum QueryMsg {
 pub struct EscrowResponse {
     pub id: u64,
     pub creator: Addr,
-    pub beneficiary: Addr,
-    pub amount: Coin,
-    pub approver1: Addr,
-    pub approver2: Addr,
-    pub approver3: Option<Addr>,
+    pub beneficiaries: Vec<(Addr, u64)>,
+    pub amount: Vec<Coin>,
+    /// Display names of the `amount` denoms, comma-separated in the same order, using the
+    /// configured aliases. Only set when at least one denom has an alias.
+    pub denom_display: Option<String>,
+    pub approvers: Vec<Addr>,
     pub description: String,
-    pub approvals: Vec<Addr>,
-    pub is_completed: bool,
+    pub approvals: Vec<Approval>,
+    pub cancel_votes: Vec<Addr>,
+    /// Effective number of approvals required to release the funds
+    pub threshold: u32,
+    pub status: EscrowStatus,
     pub created_at: u64,
     pub completed_at: Option<u64>,
+    pub expires_at: Option<u64>,
+    pub release_after: Option<u64>,
+    pub arbiter: Option<Addr>,
+    pub disputed: bool,
+    /// Whether the escrow holds its funds; unfunded escrows wait for `FundEscrow`
+    pub funded: bool,
+    /// Whether every beneficiary accepted the escrow
+    pub accepted: bool,
+    /// Whether the escrow has enough approvals and is waiting to be released
+    pub ready_to_release: bool,
+    pub accepted_by: Vec<Addr>,
+    pub accept_deadline: Option<u64>,
+    pub settlement: Option<Settlement>,
+    pub beneficiary_change: Option<BeneficiaryChange>,
+    pub depends_on: Option<u64>,
+    pub auto_approve_creator: bool,
+    pub metadata: Option<Binary>,
+    pub release_eligible_at: Option<u64>,
+    pub payout_overrides: Vec<(Addr, Addr)>,
+    pub policy: Option<ApprovalPolicy>,
+    pub approval_ttl: Option<u64>,
+    /// Weight of each approver, in the order of `approvers` (1 each unless set at creation)
+    pub approver_weights: Vec<u64>,
+    /// Total approval weight required to release, which is `threshold` for unweighted escrows
+    pub required_weight: u64,
+    pub release_hook: Option<Addr>,
+    pub release_hook_msg: Option<Binary>,
+    pub remainder_policy: RemainderPolicy,
+}
+
+#[cw_serde]
+pub struct RoleResponse {
+    pub is_creator: bool,
+    pub is_beneficiary: bool,
+    pub is_approver: bool,
+    pub has_approved: bool,
+    /// Whether an `ApproveRelease` from the address would currently be accepted
+    pub can_approve: bool,
+}
+
+#[cw_serde]
+pub struct ApprovalStatusResponse {
+    /// Number of approvals required to release the funds
+    pub required: u32,
+    /// Number of approvals given so far
+    pub current: u32,
+    /// Number of approvals still missing, zero once the escrow can be released
+    pub remaining: u32,
+    /// Approvers that approved the release
+    pub approved: Vec<Addr>,
+    /// Comments left by the approvers in `approved`, in the same order
+    pub comments: Vec<(Addr, String)>,
+    /// Approvers that can still approve, i.e. excluding the creator
+    pub pending: Vec<Addr>,
+    /// Total approval weight required to release the funds
+    pub required_weight: u64,
+    /// Summed weight of the approvals given so far
+    pub current_weight: u64,
+}
+
+#[cw_serde]
+pub struct CanCancelResponse {
+    pub can_cancel: bool,
+    /// Why the cancellation would fail, unset when it would succeed
+    pub reason: Option<String>,
+}
+
+#[cw_serde]
+pub struct ApprovalListResponse {
+    pub approvals: Vec<Approval>,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub admin: Addr,
+    pub paused: bool,
+    pub fee_recipient: Option<Addr>,
+    pub fee_bps: u16,
+    pub cancel_fee_bps: u16,
+    pub require_acceptance: bool,
+    pub max_description_length: u32,
+    pub min_amounts: Vec<Coin>,
+    pub allowed_denoms: Option<Vec<String>>,
+    pub block_beneficiary_approver: bool,
+    pub two_phase_release: bool,
+    pub grace_period: u64,
+    pub creator_allowlist_enabled: bool,
+    pub rate_limit: Option<RateLimit>,
+    pub default_threshold_policy: Option<ApprovalMode>,
+    pub approval_tiers: Vec<ApprovalTier>,
+    pub fee_rounding: FeeRounding,
+}
+
+/// Optional features enabled by the current configuration
+#[cw_serde]
+pub struct CapabilitiesResponse {
+    /// Contract name and version as stored by cw2
+    pub contract: String,
+    pub version: String,
+    /// New escrows can be created
+    pub accepting_escrows: bool,
+    /// A platform fee is taken on release
+    pub release_fee: bool,
+    pub fee_bps: u16,
+    /// A fee is taken when creators cancel
+    pub cancel_fee: bool,
+    pub cancel_fee_bps: u16,
+    /// Only the denoms in `GetConfig`'s `allowed_denoms` can be escrowed
+    pub denom_allowlist: bool,
+    /// Some denoms have a minimum escrow amount
+    pub min_amounts: bool,
+    /// Beneficiaries must accept escrows before they release
+    pub require_acceptance: bool,
+    /// Beneficiaries cannot approve their own escrows
+    pub block_beneficiary_approver: bool,
+    /// Releases have to be finalized after reaching the threshold
+    pub two_phase_release: bool,
+    pub grace_period: u64,
+    /// Only allowlisted addresses can create escrows
+    pub creator_allowlist: bool,
+    /// Escrow creations per address are rate limited
+    pub rate_limit: bool,
+    pub max_description_length: u32,
+}
+
+#[cw_serde]
+pub struct TemplateResponse {
+    pub name: String,
+    pub approvers: Vec<Addr>,
+    pub threshold: Option<u32>,
+    pub description: String,
+}
+
+#[cw_serde]
+pub struct TemplateListResponse {
+    pub templates: Vec<TemplateResponse>,
+}
+
+#[cw_serde]
+pub struct OwnershipResponse {
+    /// Current owner, the same address as the config admin
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    pub pending_expiry: Option<u64>,
+}
+
+#[cw_serde]
+pub struct EscrowEventResponse {
+    pub sequence: u64,
+    pub kind: EscrowEventKind,
+    pub actor: Addr,
+    pub timestamp: u64,
+}
+
+#[cw_serde]
+pub struct EscrowHistoryResponse {
+    pub events: Vec<EscrowEventResponse>,
+}
+
+#[cw_serde]
+pub struct ReconciliationResponse {
+    pub denom: String,
+    pub locked_in_open_escrows: Uint128,
+    /// Fees held for the fee recipient
+    pub accrued_fees: Uint128,
+    pub contract_balance: Uint128,
+    /// `contract_balance - locked_in_open_escrows - accrued_fees`. A positive difference means
+    /// funds are held outside of any open escrow, a negative one that the escrows are
+    /// underfunded.
+    pub difference: Int256,
+}
+
+#[cw_serde]
+pub struct AccruedFeesResponse {
+    pub fees: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct InvariantReport {
+    pub escrow_id: u64,
+    /// Description of each inconsistency found
+    pub violations: Vec<String>,
+}
+
+#[cw_serde]
+pub struct StatsResponse {
+    pub total_escrows: u64,
+    pub open_count: u64,
+    pub completed_count: u64,
+    /// Funds held in open escrows, one entry per denom
+    pub total_locked: Vec<Coin>,
 }
 
 #[cw_serde]
@@ -75,5 +837,31 @@ pub struct EscrowListResponse {
     pub escrows: Vec<EscrowResponse>,
 }
 
+/// Something an address can do on an escrow
+#[cw_serde]
+pub enum EscrowAction {
+    /// Approve the release, as an approver
+    Approve,
+    /// Accept the escrow, as a beneficiary
+    Accept,
+    /// Fund the escrow, as its creator
+    Fund,
+    /// Finalize the approved release, as the creator or an approver
+    Finalize,
+    /// Cancel the escrow, as its creator
+    Cancel,
+}
+
+#[cw_serde]
+pub struct ActionableEscrow {
+    pub escrow: EscrowResponse,
+    pub actions: Vec<EscrowAction>,
+}
+
+#[cw_serde]
+pub struct ActionableResponse {
+    pub escrows: Vec<ActionableEscrow>,
+}
+
 #[cw_serde]
 pub struct MigrateMsg {}
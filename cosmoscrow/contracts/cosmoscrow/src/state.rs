@@ -1,73 +1,835 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
 use cw_storage_plus::{Item, Map};
 
+#[cw_serde]
+pub enum EscrowStatus {
+    /// Waiting for approvals
+    Open,
+    /// Funds were released to the beneficiary
+    Released,
+    /// Funds were refunded to the creator on cancellation
+    Cancelled,
+    /// Funds were refunded to the creator after the escrow expired
+    Expired,
+}
+
+/// A release approval and the block time (Unix seconds) it was given at
+#[cw_serde]
+pub struct Approval {
+    pub approver: Addr,
+    pub approved_at: u64,
+    /// Reason given by the approver, if any
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
 #[cw_serde]
 pub struct Escrow {
     pub id: u64,
     pub creator: Addr,
-    pub beneficiary: Addr,
-    pub amount: Coin,
-    pub approver1: Addr,
-    pub approver2: Addr,
-    pub approver3: Option<Addr>,
+    /// Addresses receiving the released funds, each with its share weight
+    pub beneficiaries: Vec<(Addr, u64)>,
+    pub amount: Vec<Coin>,
+    /// Unique approver addresses, in the order they were given at creation
+    pub approvers: Vec<Addr>,
     pub description: String,
-    pub approvals: Vec<Addr>,
-    pub is_completed: bool,
+    pub approvals: Vec<Approval>,
+    /// Approvers that voted to cancel the escrow and refund the creator
+    pub cancel_votes: Vec<Addr>,
+    /// Explicit number of approvals required to release, if set at creation
+    pub threshold: Option<u32>,
+    pub status: EscrowStatus,
     pub created_at: u64,
     pub completed_at: Option<u64>,
+    pub expires_at: Option<u64>,
+    /// Funds are held until this time (Unix seconds) even once approved
+    pub release_after: Option<u64>,
+    /// Neutral party that settles disputes
+    pub arbiter: Option<Addr>,
+    /// Set while a dispute is open; only the arbiter can settle the escrow then
+    pub disputed: bool,
+    /// Beneficiaries that accepted the escrow
+    pub accepted_by: Vec<Addr>,
+    /// Time (Unix seconds) by which the beneficiaries must accept, after which the creator
+    /// can reclaim the funds
+    #[serde(default)]
+    pub accept_deadline: Option<u64>,
+    /// Proposed split of the funds for cancelling the escrow by agreement
+    #[serde(default)]
+    pub settlement: Option<Settlement>,
+    /// Opaque application data attached at creation, never interpreted by the contract
+    #[serde(default)]
+    pub metadata: Option<Binary>,
+    /// With two-phase release and a grace period, the time (Unix seconds) from which the
+    /// release can be finalized by anyone. Set when the approval threshold is reached.
+    #[serde(default)]
+    pub release_eligible_at: Option<u64>,
+    /// Beneficiaries that asked for their share to be sent to another address, each with
+    /// that address
+    #[serde(default)]
+    pub payout_overrides: Vec<(Addr, Addr)>,
+    /// Governance model declared at creation, which sets the required approvals
+    #[serde(default)]
+    pub policy: Option<ApprovalPolicy>,
+    /// Seconds an approval counts towards the release, after which it has to be renewed
+    #[serde(default)]
+    pub approval_ttl: Option<u64>,
+    /// Weight of each approver, in the order of `approvers`. Empty when every approver
+    /// counts once.
+    #[serde(default)]
+    pub approver_weights: Vec<u64>,
+    /// Total weight of approvals required to release, replacing the approval count
+    #[serde(default)]
+    pub required_weight: Option<u64>,
+    /// Number of approvals required to release, fixed at creation so that later changes to
+    /// the approvers or the config never move it. Only funding and top-ups, which happen
+    /// before any approval, can raise it to an approval tier. Zero only for escrows stored
+    /// before v0.7.0 until they are migrated.
+    #[serde(default)]
+    pub required_approvals: u32,
+    /// Contract executed with `release_hook_msg` when the funds are released
+    #[serde(default)]
+    pub release_hook: Option<Addr>,
+    #[serde(default)]
+    pub release_hook_msg: Option<Binary>,
+    /// Which beneficiaries receive the rounding leftover of a split payout
+    #[serde(default)]
+    pub remainder_policy: RemainderPolicy,
+    /// Pending swap of a beneficiary, which holds back the release until it is resolved
+    #[serde(default)]
+    pub beneficiary_change: Option<BeneficiaryChange>,
+    /// Earlier escrow that must be released before this one can be
+    #[serde(default)]
+    pub depends_on: Option<u64>,
+    /// Whether the creator is one of the approvers and was counted as approving at creation
+    #[serde(default)]
+    pub auto_approve_creator: bool,
+    /// Exact amount the escrow must be funded with, checked again when an unfunded escrow
+    /// gets its funds
+    #[serde(default)]
+    pub expected_amount: Option<Coin>,
+}
+
+/// Governance model of an escrow, fixing how many approvers it has and how many of them
+/// must approve
+#[cw_serde]
+pub enum ApprovalPolicy {
+    /// Exactly three approvers, any two of which release the funds
+    TwoOfThree,
+    /// Every approver other than the creator
+    Unanimous,
+    /// Exactly one approver
+    Single,
+    /// Any approvers, the given number of which release the funds
+    Custom(u32),
+}
+
+/// Who receives the leftover when a payout split by weight does not divide evenly
+#[cw_serde]
+#[derive(Default)]
+pub enum RemainderPolicy {
+    /// The first beneficiary gets all of it
+    #[default]
+    FirstBeneficiary,
+    /// The last beneficiary gets all of it
+    LastBeneficiary,
+    /// One unit each to the beneficiaries whose exact share was rounded down the most
+    ProRataLargest,
+}
+
+/// How many approvals an escrow needs, relative to its approvers
+#[cw_serde]
+pub enum ApprovalMode {
+    /// Every approver other than the creator
+    Unanimous,
+    /// A simple majority of the approvers
+    Majority,
+    /// Any single approver
+    Single,
+}
+
+/// A proposal by the creator to swap a beneficiary, applied once as many approvers as the
+/// release needs agreed to it
+#[cw_serde]
+pub struct BeneficiaryChange {
+    pub old: Addr,
+    pub new: Addr,
+    /// Approvers that agreed to the change
+    pub approved_by: Vec<Addr>,
+}
+
+/// A proposal to cancel an escrow with part of the funds going to the beneficiaries
+#[cw_serde]
+pub struct Settlement {
+    /// Funds split between the beneficiaries by weight; the rest is refunded to the creator
+    pub to_beneficiary: Vec<Coin>,
+    /// Parties that agreed to this split
+    pub agreed_by: Vec<Addr>,
 }
 
 impl Escrow {
+    /// Escrows created unfunded hold no funds until their creator funds them
+    pub fn is_funded(&self) -> bool {
+        !self.amount.is_empty()
+    }
+
+    pub fn is_beneficiary(&self, addr: &Addr) -> bool {
+        self.beneficiaries.iter().any(|(beneficiary, _)| beneficiary == addr)
+    }
+
+    /// Whether every beneficiary accepted the escrow
+    pub fn is_accepted(&self) -> bool {
+        self.beneficiaries
+            .iter()
+            .all(|(beneficiary, _)| self.accepted_by.contains(beneficiary))
+    }
+
+    /// Address receiving a beneficiary's share, unless they set another one
+    pub fn payout_address<'a>(&'a self, beneficiary: &'a Addr) -> &'a Addr {
+        self.payout_overrides
+            .iter()
+            .find(|(addr, _)| addr == beneficiary)
+            .map_or(beneficiary, |(_, payout_address)| payout_address)
+    }
+
     pub fn is_approver(&self, addr: &Addr) -> bool {
-        &self.approver1 == addr 
-            || &self.approver2 == addr 
-            || self.approver3.as_ref() == Some(addr)
+        self.approvers.contains(addr)
     }
 
     pub fn has_approved(&self, addr: &Addr) -> bool {
-        self.approvals.contains(addr)
+        self.approvals.iter().any(|approval| approval.approver == *addr)
+    }
+
+    /// Whether an approval still counts, i.e. it was given within the approval TTL
+    pub fn is_approval_current(&self, approval: &Approval, now: u64) -> bool {
+        match self.approval_ttl {
+            Some(approval_ttl) => approval.approved_at >= now.saturating_sub(approval_ttl),
+            None => true,
+        }
+    }
+
+    /// Whether the address gave an approval that still counts
+    pub fn has_current_approval(&self, addr: &Addr, now: u64) -> bool {
+        self.approvals
+            .iter()
+            .any(|approval| approval.approver == *addr && self.is_approval_current(approval, now))
+    }
+
+    /// Number of approvals that still count
+    pub fn current_approvals(&self, now: u64) -> usize {
+        self.approvals
+            .iter()
+            .filter(|approval| self.is_approval_current(approval, now))
+            .count()
+    }
+
+    /// Weight the address's approval carries, 1 unless weights were set at creation. Addresses
+    /// that are not approvers carry no weight.
+    pub fn approver_weight(&self, addr: &Addr) -> u64 {
+        match self.approvers.iter().position(|approver| approver == addr) {
+            Some(index) => self.approver_weights.get(index).copied().unwrap_or(1),
+            None => 0,
+        }
+    }
+
+    /// Summed weight of the approvals that still count
+    pub fn current_weight(&self, now: u64) -> u64 {
+        self.approvals
+            .iter()
+            .filter(|approval| self.is_approval_current(approval, now))
+            .map(|approval| self.approver_weight(&approval.approver))
+            .sum()
+    }
+
+    /// Total weight of approvals needed to release. Without an explicit required weight this
+    /// is the number of required approvals, so unweighted escrows keep counting approvals.
+    pub fn release_weight(&self) -> u64 {
+        self.required_weight.unwrap_or(self.required_approvals() as u64)
+    }
+
+    /// Summed weight of the approvers that are allowed to approve
+    pub fn eligible_weight(&self) -> u64 {
+        self.approvers
+            .iter()
+            .filter(|addr| self.is_eligible(addr))
+            .map(|addr| self.approver_weight(addr))
+            .sum()
+    }
+
+    pub fn has_voted_cancel(&self, addr: &Addr) -> bool {
+        self.cancel_votes.contains(addr)
     }
 
+    /// Number of approvals required to release, as fixed at creation
     pub fn required_approvals(&self) -> usize {
-        // Determine number of unique approver addresses
-        let mut unique_approvers: Vec<&Addr> = vec![&self.approver1, &self.approver2];
-        if let Some(ref a3) = self.approver3 { unique_approvers.push(a3); }
-        unique_approvers.sort();
-        unique_approvers.dedup();
+        self.required_approvals as usize
+    }
 
-        match unique_approvers.len() {
-            0 => 0,
-            1 => 1,          // If there is only one unique approver, require just one approval
-            2 => 2,          // If there are two unique approvers, require both approvals
-            _ => 2,          // If there are three unique approvers, require 2 of 3 approvals
+    /// Number of approvals the escrow's rules ask for given its current approvers. Only used
+    /// to fix `required_approvals` when the escrow is created.
+    pub fn rule_required_approvals(&self) -> usize {
+        match self.policy {
+            Some(ApprovalPolicy::TwoOfThree) => return 2,
+            Some(ApprovalPolicy::Unanimous) => return self.eligible_approvers(),
+            Some(ApprovalPolicy::Single) => return 1,
+            Some(ApprovalPolicy::Custom(threshold)) => return threshold as usize,
+            None => {}
+        }
+
+        if let Some(threshold) = self.threshold {
+            return threshold as usize;
+        }
+
+        self.mode_required_approvals(&ApprovalMode::Majority)
+    }
+
+    /// Number of approvals the mode asks for given the current approvers
+    pub fn mode_required_approvals(&self, mode: &ApprovalMode) -> usize {
+        match mode {
+            ApprovalMode::Unanimous => self.eligible_approvers(),
+            // A simple majority of the unique approvers: 1-of-1, both-of-2, 2-of-3, 3-of-4,
+            // 3-of-5, ... The creator cannot approve their own escrow, so never require more
+            // approvals than the remaining approvers can give.
+            ApprovalMode::Majority => (self.total_approvers() / 2 + 1).min(self.eligible_approvers()),
+            ApprovalMode::Single => 1,
         }
     }
 
     pub fn total_approvers(&self) -> usize {
-        let mut unique_approvers: Vec<&Addr> = vec![&self.approver1, &self.approver2];
-        if let Some(ref a3) = self.approver3 { unique_approvers.push(a3); }
+        self.unique_approvers().len()
+    }
+
+    /// Number of unique approvers that are allowed to approve
+    pub fn eligible_approvers(&self) -> usize {
+        self.unique_approvers()
+            .into_iter()
+            .filter(|addr| self.is_eligible(addr))
+            .count()
+    }
+
+    /// Whether the address is allowed to approve if it is an approver. The creator cannot
+    /// approve their own escrow, unless they were auto-approved at creation.
+    pub fn is_eligible(&self, addr: &Addr) -> bool {
+        *addr != self.creator || self.auto_approve_creator
+    }
+
+    /// Whether anyone besides the creator approved. An auto-approved creator still has the
+    /// escrow to themselves until then.
+    pub fn has_outside_approvals(&self) -> bool {
+        self.approvals.iter().any(|approval| approval.approver != self.creator)
+    }
+
+    fn unique_approvers(&self) -> Vec<&Addr> {
+        let mut unique_approvers: Vec<&Addr> = self.approvers.iter().collect();
         unique_approvers.sort();
         unique_approvers.dedup();
-        unique_approvers.len()
+        unique_approvers
+    }
+
+    /// Whether the escrow reached a terminal state (released, cancelled or expired)
+    pub fn is_completed(&self) -> bool {
+        self.status != EscrowStatus::Open
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        matches!(self.expires_at, Some(expires_at) if now > expires_at)
+    }
+
+    /// Whether the acceptance deadline passed without every beneficiary accepting
+    pub fn is_acceptance_overdue(&self, now: u64) -> bool {
+        !self.is_accepted() && matches!(self.accept_deadline, Some(deadline) if now > deadline)
     }
 
-    pub fn can_be_released(&self) -> bool {
-        !self.is_completed && self.approvals.len() >= self.required_approvals()
+    /// Whether the funds are still locked by `release_after`
+    pub fn is_time_locked(&self, now: u64) -> bool {
+        matches!(self.release_after, Some(release_after) if now < release_after)
     }
+
+    pub fn can_be_released(&self, now: u64) -> bool {
+        !self.is_completed() && self.current_weight(now) >= self.release_weight()
+    }
+
+    /// Cancelling requires the same approver weight as releasing
+    pub fn can_be_cancelled(&self) -> bool {
+        let weight: u64 = self.cancel_votes.iter().map(|addr| self.approver_weight(addr)).sum();
+        !self.is_completed() && weight >= self.release_weight()
+    }
+}
+
+#[cw_serde]
+pub struct Config {
+    /// Address allowed to perform admin actions such as pausing
+    pub admin: Addr,
+    /// When paused, no new escrows can be created. Existing escrows keep working.
+    pub paused: bool,
+    /// Address receiving the platform fee on release
+    pub fee_recipient: Option<Addr>,
+    /// Platform fee in basis points of the released amount
+    pub fee_bps: u16,
+    /// Fee in basis points of the refund taken when a creator cancels an escrow
+    #[serde(default)]
+    pub cancel_fee_bps: u16,
+    /// When set, escrows only release after all beneficiaries have accepted them
+    #[serde(default)]
+    pub require_acceptance: bool,
+    /// Maximum escrow description length, in characters
+    #[serde(default = "default_max_description_length")]
+    pub max_description_length: u32,
+    /// Smallest amount an escrow can be created with, per denom. Denoms not listed
+    /// have no minimum.
+    #[serde(default)]
+    pub min_amounts: Vec<Coin>,
+    /// Denoms escrows can be created with. `None` allows every denom.
+    #[serde(default)]
+    pub allowed_denoms: Option<Vec<String>>,
+    /// When set, beneficiaries cannot also be approvers of their escrow
+    #[serde(default)]
+    pub block_beneficiary_approver: bool,
+    /// When set, reaching the approval threshold does not release the funds. A party to
+    /// the escrow has to finalize the release, and approvals can be revoked until then.
+    #[serde(default)]
+    pub two_phase_release: bool,
+    /// Seconds between reaching the approval threshold and the release becoming final
+    /// under two-phase release, during which a dispute can still be raised
+    #[serde(default)]
+    pub grace_period: u64,
+    /// When set, only addresses in `CREATOR_ALLOWLIST` can create escrows
+    #[serde(default)]
+    pub creator_allowlist_enabled: bool,
+    /// Limit on how many escrows one address can create per time window
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+    /// Threshold given to escrows created without one. `None` keeps the simple majority.
+    #[serde(default)]
+    pub default_threshold_policy: Option<ApprovalMode>,
+    /// Stricter approval requirements for high-value escrows. The strictest tier the escrowed
+    /// amount reaches applies; escrows below every tier keep their own threshold.
+    #[serde(default)]
+    pub approval_tiers: Vec<ApprovalTier>,
+    /// How fractional fees are rounded, for both the release and the cancellation fee
+    #[serde(default)]
+    pub fee_rounding: FeeRounding,
+}
+
+/// Rounding of fees that come out at a fraction of a unit
+#[cw_serde]
+#[derive(Default)]
+pub enum FeeRounding {
+    /// Round down, in favour of the payer
+    #[default]
+    Floor,
+    /// Round up, in favour of the fee recipient
+    Ceil,
+    /// Round to the nearest unit, and halves to the even one (bankers' rounding)
+    HalfEven,
+}
+
+/// Escrows holding at least `min_amount` of `denom` need at least the approvals `mode` asks
+/// for, whatever threshold they were created with
+#[cw_serde]
+pub struct ApprovalTier {
+    pub denom: String,
+    pub min_amount: Uint128,
+    pub mode: ApprovalMode,
+}
+
+/// At most `max_creations` escrows per address in each window of `window` seconds
+#[cw_serde]
+pub struct RateLimit {
+    pub max_creations: u32,
+    pub window: u64,
+}
+
+/// Default maximum description length, in characters
+pub const DEFAULT_MAX_DESCRIPTION_LENGTH: u32 = 500;
+
+fn default_max_description_length() -> u32 {
+    DEFAULT_MAX_DESCRIPTION_LENGTH
+}
+
+/// Contract-wide configuration set at instantiation
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Addresses allowed to create escrows while the creator allowlist is enabled
+pub const CREATOR_ALLOWLIST: Map<&Addr, ()> = Map::new("creator_allowlist");
+
+/// An ownership transfer proposed by the owner and not yet accepted
+#[cw_serde]
+pub struct PendingOwner {
+    pub new_owner: Addr,
+    /// Time (Unix seconds) after which the proposal can no longer be accepted
+    pub expiry: Option<u64>,
+}
+
+/// Display names for denoms, such as `ibc/...` hashes, keyed by denom. Only used in
+/// query responses.
+pub const DENOM_ALIASES: Map<&str, String> = Map::new("denom_aliases");
+
+/// The pending ownership transfer, if any. The current owner is `Config::admin`.
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+
+/// Time (seconds) between proposing an emergency withdrawal and being able to execute it
+pub const EMERGENCY_WITHDRAW_DELAY: u64 = 7 * 24 * 60 * 60;
+
+/// An emergency withdrawal of an escrow's funds proposed by the owner
+#[cw_serde]
+pub struct EmergencyWithdraw {
+    pub to: Addr,
+    /// Time (Unix seconds) the withdrawal was proposed at
+    pub proposed_at: u64,
+}
+
+/// Pending emergency withdrawals keyed by escrow ID
+pub const EMERGENCY_WITHDRAWALS: Map<u64, EmergencyWithdraw> = Map::new("emergency_withdrawals");
+
+/// Reusable escrow settings saved by a user
+#[cw_serde]
+pub struct Template {
+    pub approvers: Vec<Addr>,
+    pub threshold: Option<u32>,
+    pub description: String,
+}
+
+/// Escrow templates keyed by `(owner, name)`
+pub const TEMPLATES: Map<(&Addr, &str), Template> = Map::new("templates");
+
+/// Address allowed to approve a release on an approver's behalf, keyed by
+/// `(escrow_id, approver)`
+pub const APPROVAL_DELEGATES: Map<(u64, &Addr), Addr> = Map::new("approval_delegates");
+
+/// secp256k1 public key an address registered to sign approvals submitted by others
+pub const APPROVAL_KEYS: Map<&Addr, Binary> = Map::new("approval_keys");
+
+/// Number of times each approver approved or revoked an escrow, keyed by
+/// `(escrow_id, approver)`. Signed approvals name the current value, so a signature is only
+/// good until the approver's next approval or revocation.
+pub const APPROVAL_NONCES: Map<(u64, &Addr), u64> = Map::new("approval_nonces");
+
+/// Kind of change recorded in an escrow's history
+#[cw_serde]
+pub enum EscrowEventKind {
+    Created,
+    Funded,
+    Approved,
+    ApprovalRevoked,
+    CancelVoted,
+    Accepted,
+    PayoutAddressSet,
+    Disputed,
+    SettlementProposed,
+    CreatorTransferred,
+    ApproverReplaced,
+    BeneficiaryChangeProposed,
+    BeneficiaryChangeCancelled,
+    BeneficiaryChanged,
+    ToppedUp,
+    DescriptionUpdated,
+    EmergencyWithdrawProposed,
+    Released,
+    ReleaseFailed,
+    Cancelled,
+    Expired,
+}
+
+/// A change to an escrow, who made it and the block time (Unix seconds) it was made at
+#[cw_serde]
+pub struct EscrowEvent {
+    pub kind: EscrowEventKind,
+    pub actor: Addr,
+    pub timestamp: u64,
 }
 
+/// Append-only history of each escrow, keyed by `(escrow_id, sequence)` with sequences
+/// starting at 0
+pub const ESCROW_HISTORY: Map<(u64, u64), EscrowEvent> = Map::new("escrow_history");
+
+/// Maximum length of an approval comment, in characters
+pub const MAX_COMMENT_LENGTH: usize = 280;
+
+/// Maximum size of an escrow's metadata, in bytes
+pub const MAX_METADATA_SIZE: usize = 2048;
+
+/// Maximum number of beneficiaries sharing an escrow
+pub const MAX_BENEFICIARIES: usize = 10;
+
+/// Maximum number of escrows handled by one batch message or query
+pub const MAX_BATCH_SIZE: usize = 30;
+
+/// Maximum number of approvers a single escrow can have
+pub const MAX_APPROVERS: usize = 10;
+
+/// Maximum number of escrows a query sorting them in memory ranks and returns
+pub const MAX_SORT_CANDIDATES: usize = 50;
+
+/// Maximum number of index entries a query sorting escrows in memory looks at to find its
+/// candidates
+pub const MAX_SORT_SCAN: usize = 200;
+
 /// Counter for generating unique escrow IDs
 pub const ESCROW_COUNTER: Item<u64> = Item::new("escrow_counter");
 
+/// Running escrow counts, updated whenever an escrow is created or completed
+#[cw_serde]
+#[derive(Default)]
+pub struct Stats {
+    pub total_escrows: u64,
+    pub open_count: u64,
+    pub completed_count: u64,
+    /// Completed escrows by status, adding up to `completed_count`
+    #[serde(default)]
+    pub released_count: u64,
+    #[serde(default)]
+    pub cancelled_count: u64,
+    #[serde(default)]
+    pub expired_count: u64,
+}
+
+impl Stats {
+    /// The counter of escrows with the given status
+    pub fn status_count_mut(&mut self, status: &EscrowStatus) -> &mut u64 {
+        match status {
+            EscrowStatus::Open => &mut self.open_count,
+            EscrowStatus::Released => &mut self.released_count,
+            EscrowStatus::Cancelled => &mut self.cancelled_count,
+            EscrowStatus::Expired => &mut self.expired_count,
+        }
+    }
+}
+
+pub const STATS: Item<Stats> = Item::new("stats");
+
+/// Amount currently held in open escrows, per denom
+pub const TOTAL_LOCKED: Map<&str, Uint128> = Map::new("total_locked");
+
+/// Fees collected by the contract that the fee recipient has not withdrawn yet, per denom
+pub const FEE_ACCRUED: Map<&str, Uint128> = Map::new("fee_accrued");
+
 /// Map from escrow ID to escrow data
 pub const ESCROWS: Map<u64, Escrow> = Map::new("escrows");
 
-/// Map from creator address to list of escrow IDs they created
-pub const ESCROWS_BY_CREATOR: Map<&Addr, Vec<u64>> = Map::new("escrows_by_creator");
+/// Escrow creations per sender in the current rate limit window, as
+/// `(window_start, count)`
+pub const CREATION_COUNTS: Map<&Addr, (u64, u32)> = Map::new("creation_counts");
+
+/// Escrow IDs by (creator, idempotency key), used to detect retried creations
+pub const IDEMPOTENCY_KEYS: Map<(&Addr, &str), u64> = Map::new("idempotency_keys");
+
+/// Index of escrow IDs by creator address, keyed by (creator, escrow_id). Like the other
+/// address and denom indexes, it keeps escrows whatever their status, released, cancelled
+/// and expired ones included.
+pub const ESCROWS_BY_CREATOR: Map<(&Addr, u64), ()> = Map::new("creator_escrows");
+
+/// Index of escrow IDs by beneficiary address, keyed by (beneficiary, escrow_id)
+pub const ESCROWS_BY_BENEFICIARY: Map<(&Addr, u64), ()> = Map::new("beneficiary_escrows");
+
+/// Index of escrow IDs by approver address, keyed by (approver, escrow_id)
+pub const ESCROWS_BY_APPROVER: Map<(&Addr, u64), ()> = Map::new("approver_escrows");
+
+/// Index of escrow IDs by the denoms they hold, keyed by (denom, escrow_id)
+pub const ESCROWS_BY_DENOM: Map<(&str, u64), ()> = Map::new("denom_escrows");
+
+/// Index of escrow IDs by creation time, keyed by (created_at, escrow_id). Escrows stay in
+/// this index for good, whatever their status.
+pub const ESCROWS_BY_CREATED_AT: Map<(u64, u64), ()> = Map::new("created_at_escrows");
+
+/// Index of open escrow IDs by expiration time, keyed by (expires_at, escrow_id). Escrows
+/// without an expiration are left out, and escrows leave the index once disputed or completed.
+pub const ESCROWS_BY_EXPIRES_AT: Map<(u64, u64), ()> = Map::new("expires_at_escrows");
+
+/// Per-address lists of escrow IDs used up to v0.2.0, only used to clean up old data
+pub const LEGACY_ESCROWS_BY_CREATOR: Map<&Addr, Vec<u64>> = Map::new("escrows_by_creator");
+pub const LEGACY_ESCROWS_BY_BENEFICIARY: Map<&Addr, Vec<u64>> = Map::new("escrows_by_beneficiary");
+pub const LEGACY_ESCROWS_BY_APPROVER: Map<&Addr, Vec<u64>> = Map::new("escrows_by_approver");
+
+/// Escrow layout stored by v0.1.0 of the contract, only used to migrate old data
+#[cw_serde]
+pub struct EscrowV1 {
+    pub id: u64,
+    pub creator: Addr,
+    pub beneficiary: Addr,
+    pub amount: Coin,
+    pub approver1: Addr,
+    pub approver2: Addr,
+    pub approver3: Option<Addr>,
+    pub description: String,
+    pub approvals: Vec<Addr>,
+    pub is_completed: bool,
+    pub created_at: u64,
+    pub completed_at: Option<u64>,
+}
+
+impl From<EscrowV1> for EscrowV2 {
+    fn from(old: EscrowV1) -> Self {
+        let mut approvers: Vec<Addr> = vec![];
+        for addr in [Some(old.approver1), Some(old.approver2), old.approver3].into_iter().flatten() {
+            if !approvers.contains(&addr) {
+                approvers.push(addr);
+            }
+        }
+
+        // v0.1.0 set `is_completed` on both release and cancel, but only recorded
+        // `completed_at` when releasing
+        let status = match (old.is_completed, old.completed_at) {
+            (false, _) => EscrowStatus::Open,
+            (true, Some(_)) => EscrowStatus::Released,
+            (true, None) => EscrowStatus::Cancelled,
+        };
 
-/// Map from beneficiary address to list of escrow IDs where they are the beneficiary
-pub const ESCROWS_BY_BENEFICIARY: Map<&Addr, Vec<u64>> = Map::new("escrows_by_beneficiary");
+        EscrowV2 {
+            id: old.id,
+            creator: old.creator,
+            beneficiary: old.beneficiary,
+            amount: vec![old.amount],
+            approvers,
+            description: old.description,
+            approvals: old.approvals,
+            cancel_votes: vec![],
+            threshold: None,
+            status,
+            created_at: old.created_at,
+            completed_at: old.completed_at,
+            expires_at: None,
+            arbiter: None,
+            disputed: false,
+            accepted: false,
+        }
+    }
+}
+
+impl From<EscrowV1> for Escrow {
+    fn from(old: EscrowV1) -> Self {
+        EscrowV2::from(old).into()
+    }
+}
+
+/// v0.1.0 escrows, stored under the same namespace as `ESCROWS`
+pub const ESCROWS_V1: Map<u64, EscrowV1> = Map::new("escrows");
+
+/// Escrow layout stored by v0.2.0 and v0.3.0, which recorded approvals without a timestamp
+#[cw_serde]
+pub struct EscrowV2 {
+    pub id: u64,
+    pub creator: Addr,
+    pub beneficiary: Addr,
+    pub amount: Vec<Coin>,
+    pub approvers: Vec<Addr>,
+    pub description: String,
+    pub approvals: Vec<Addr>,
+    pub cancel_votes: Vec<Addr>,
+    pub threshold: Option<u32>,
+    pub status: EscrowStatus,
+    pub created_at: u64,
+    pub completed_at: Option<u64>,
+    pub expires_at: Option<u64>,
+    pub arbiter: Option<Addr>,
+    #[serde(default)]
+    pub disputed: bool,
+    #[serde(default)]
+    pub accepted: bool,
+}
+
+impl From<EscrowV2> for EscrowV4 {
+    fn from(old: EscrowV2) -> Self {
+        EscrowV4 {
+            id: old.id,
+            creator: old.creator,
+            beneficiary: old.beneficiary,
+            amount: old.amount,
+            approvers: old.approvers,
+            description: old.description,
+            // Approvals stored before v0.4.0 have no timestamp, so they are recorded at time 0
+            approvals: old
+                .approvals
+                .into_iter()
+                .map(|approver| Approval { approver, approved_at: 0, comment: None })
+                .collect(),
+            cancel_votes: old.cancel_votes,
+            threshold: old.threshold,
+            status: old.status,
+            created_at: old.created_at,
+            completed_at: old.completed_at,
+            expires_at: old.expires_at,
+            release_after: None,
+            arbiter: old.arbiter,
+            disputed: old.disputed,
+            accepted: old.accepted,
+        }
+    }
+}
+
+impl From<EscrowV2> for Escrow {
+    fn from(old: EscrowV2) -> Self {
+        EscrowV4::from(old).into()
+    }
+}
+
+/// v0.2.0 and v0.3.0 escrows, stored under the same namespace as `ESCROWS`
+pub const ESCROWS_V2: Map<u64, EscrowV2> = Map::new("escrows");
+
+/// Escrow layout stored by v0.4.0, which had a single beneficiary
+#[cw_serde]
+pub struct EscrowV4 {
+    pub id: u64,
+    pub creator: Addr,
+    pub beneficiary: Addr,
+    pub amount: Vec<Coin>,
+    pub approvers: Vec<Addr>,
+    pub description: String,
+    pub approvals: Vec<Approval>,
+    pub cancel_votes: Vec<Addr>,
+    pub threshold: Option<u32>,
+    pub status: EscrowStatus,
+    pub created_at: u64,
+    pub completed_at: Option<u64>,
+    pub expires_at: Option<u64>,
+    pub release_after: Option<u64>,
+    pub arbiter: Option<Addr>,
+    pub disputed: bool,
+    pub accepted: bool,
+}
+
+impl From<EscrowV4> for Escrow {
+    fn from(old: EscrowV4) -> Self {
+        let accepted_by = if old.accepted { vec![old.beneficiary.clone()] } else { vec![] };
+        Escrow {
+            id: old.id,
+            creator: old.creator,
+            beneficiaries: vec![(old.beneficiary, 1)],
+            amount: old.amount,
+            approvers: old.approvers,
+            description: old.description,
+            approvals: old.approvals,
+            cancel_votes: old.cancel_votes,
+            threshold: old.threshold,
+            status: old.status,
+            created_at: old.created_at,
+            completed_at: old.completed_at,
+            expires_at: old.expires_at,
+            release_after: old.release_after,
+            arbiter: old.arbiter,
+            disputed: old.disputed,
+            accepted_by,
+            accept_deadline: None,
+            settlement: None,
+            metadata: None,
+            release_eligible_at: None,
+            payout_overrides: vec![],
+            policy: None,
+            approval_ttl: None,
+            approver_weights: vec![],
+            required_weight: None,
+            required_approvals: 0,
+            release_hook: None,
+            remainder_policy: RemainderPolicy::default(),
+            beneficiary_change: None,
+            depends_on: None,
+            auto_approve_creator: false,
+            expected_amount: None,
+            release_hook_msg: None,
+        }
+    }
+}
 
-/// Map from approver address to list of escrow IDs where they are an approver
-pub const ESCROWS_BY_APPROVER: Map<&Addr, Vec<u64>> = Map::new("escrows_by_approver");
+/// v0.4.0 escrows, stored under the same namespace as `ESCROWS`
+pub const ESCROWS_V4: Map<u64, EscrowV4> = Map::new("escrows");